@@ -0,0 +1,148 @@
+#[derive(Debug, PartialEq, Default)]
+pub struct Attributes {
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+    pub pairs: Vec<(String, String)>,
+    pub number: Option<String>,
+}
+
+impl Attributes {
+    pub fn parse(text: &str) -> Option<Attributes> {
+        let mut attributes = Attributes::default();
+        let mut rest = text.trim();
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        while !rest.is_empty() {
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+            if &rest[..end] == "-" {
+                attributes.classes.push("unnumbered".to_string());
+            } else if let Some(id) = rest[..end].strip_prefix('#') {
+                Self::validate_name(id)?;
+                attributes.id = Some(id.to_string());
+            } else if let Some(class) = rest[..end].strip_prefix('.') {
+                Self::validate_name(class)?;
+                attributes.classes.push(class.to_string());
+            } else {
+                let (key, value) = rest.split_once('=')?;
+                Self::validate_name(key)?;
+
+                let (value, length) = match value.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let close = value[1..].find(quote)?;
+                        (&value[1..close + 1], close + 2)
+                    }
+                    _ => {
+                        let end = value.find(char::is_whitespace).unwrap_or(value.len());
+                        (&value[..end], end)
+                    }
+                };
+
+                attributes.pairs.push((key.to_string(), value.to_string()));
+                rest = &rest[key.len() + 1 + length..];
+                if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+                    return None;
+                }
+                rest = rest.trim_start();
+                continue;
+            }
+
+            rest = rest[end..].trim_start();
+        }
+
+        Some(attributes)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(other, _)| other == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn has_class(&self, class: &str) -> bool {
+        self.classes.iter().any(|other| other == class)
+    }
+
+    pub fn retain_safe(&mut self) {
+        self.pairs.retain(|(key, _)| {
+            let key = key.to_ascii_lowercase();
+            !key.starts_with("on") && !matches!(key.as_str(), "style" | "srcdoc" | "formaction")
+        });
+    }
+
+    pub fn split_trailing(text: &str) -> Option<(&str, Attributes)> {
+        let trimmed = text.trim_end();
+        let inner = trimmed.strip_suffix('}')?;
+        let start = inner.rfind('{')?;
+
+        if !inner[..start].is_empty() && !inner[..start].ends_with(char::is_whitespace) {
+            return None;
+        }
+
+        Attributes::parse(&inner[start + 1..]).map(|attributes| (&text[..start], attributes))
+    }
+
+    pub fn split_leading(text: &str) -> Option<(Attributes, &str)> {
+        let inner = text.strip_prefix('{')?;
+        let end = inner.find('}')?;
+
+        Attributes::parse(&inner[..end]).map(|attributes| (attributes, &inner[end + 1..]))
+    }
+
+    fn validate_name(name: &str) -> Option<()> {
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|char| char.is_alphanumeric() || matches!(char, '-' | '_' | ':' | '.'))
+        {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_attributes() {
+        assert_eq!(
+            Attributes::parse("#setup .important .wide data-x=1 title=\"Hello world\""),
+            Some(Attributes {
+                id: Some("setup".to_string()),
+                classes: vec!["important".to_string(), "wide".to_string()],
+                pairs: vec![
+                    ("data-x".to_string(), "1".to_string()),
+                    ("title".to_string(), "Hello world".to_string()),
+                ],
+                ..Attributes::default()
+            })
+        );
+        assert!(
+            Attributes::parse("- .x").is_some_and(|attributes| attributes.has_class("unnumbered"))
+        );
+        assert_eq!(Attributes::parse(""), None);
+        assert_eq!(Attributes::parse("not attributes"), None);
+        assert_eq!(Attributes::parse("title=\"unclosed"), None);
+    }
+
+    #[test]
+    fn splits_attributes() {
+        let (text, attributes) = Attributes::split_trailing("Install {#setup}").unwrap();
+        assert_eq!(text, "Install ");
+        assert_eq!(attributes.id, Some("setup".to_string()));
+
+        assert_eq!(Attributes::split_trailing("a{#b}"), None);
+        assert_eq!(Attributes::split_trailing("set {x}"), None);
+
+        let (attributes, rest) = Attributes::split_leading("{.button} rest").unwrap();
+        assert_eq!(attributes.classes, vec!["button".to_string()]);
+        assert_eq!(rest, " rest");
+    }
+}
@@ -1,172 +1,761 @@
-use crate::parser::{Expression, Parser, Statement};
+use crate::attributes::Attributes;
+use crate::front_matter::FrontMatter;
+use crate::lexer::split_lines;
+use crate::options::{Options, RawHtml};
+use crate::parser::{Alignment, Expression, Parser, Statement, Task};
+use crate::renderer::escape;
+use crate::section;
+use crate::slug::Slugger;
+use crate::template::{self, Sink, Template};
+use crate::toc::{self, TocEntry};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt;
+use std::io;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+pub struct Document {
+    pub front_matter: FrontMatter,
+    pub title: Option<String>,
+    pub html: String,
+}
 
 pub struct Evaluator {
+    options: Options,
+    front_matter: FrontMatter,
+    title: Option<String>,
     statements: Vec<Statement>,
+    footnote_definitions: HashMap<String, Vec<Statement>>,
+    footnote_references: Vec<(String, usize)>,
+    toc: Vec<TocEntry>,
+    heading_numbers: HashMap<String, String>,
 }
 
 impl Evaluator {
     pub fn new(input: String) -> Evaluator {
-        let parser = Parser::new(input);
-        let statements = parser.parse();
+        Self::with_options(input, Options::default())
+    }
+
+    pub fn with_options(input: String, options: Options) -> Evaluator {
+        let (front_matter, input, first_line) = match FrontMatter::extract(&input) {
+            Some((front_matter, body)) => {
+                let skipped = split_lines(&input[..input.len() - body.len()]).count();
+                (front_matter, body.to_string(), skipped + 1)
+            }
+            None => (FrontMatter::default(), input, 1),
+        };
+        let parser = Parser::with_first_line(input, first_line);
+        let mut parsed = parser.parse();
+        let extracted = if options.extract_title {
+            Self::extract_title(&mut parsed)
+        } else {
+            None
+        };
+        if options.heading_offset > 0 {
+            toc::for_each_heading(&mut parsed, &mut |level, _, _| {
+                *level = (*level + options.heading_offset).min(6);
+            });
+        }
+        Self::assign_heading_ids(&mut parsed, options.heading_ids);
+        let heading_numbers = if options.heading_numbers {
+            Self::number_headings(&mut parsed, &options)
+        } else {
+            HashMap::new()
+        };
+
+        let mut statements = Vec::new();
+        let mut footnote_definitions = HashMap::new();
+
+        for statement in parsed {
+            match statement {
+                Statement::FootnoteDefinition(label, statements) => {
+                    footnote_definitions.entry(label).or_insert(statements);
+                }
+                statement => statements.push(statement),
+            }
+        }
+
+        let title = front_matter.title().or(extracted).or_else(|| {
+            statements.iter().find_map(|statement| match statement {
+                Statement::Heading(_, expression, _) => {
+                    Some(expression.plain_text().trim().to_string())
+                }
+                _ => None,
+            })
+        });
+        let toc = toc::outline(&statements, options.toc_min_depth, options.toc_max_depth);
+        if options.sections {
+            statements = section::build(statements);
+        }
+
+        Evaluator {
+            options,
+            front_matter,
+            title,
+            statements,
+            footnote_definitions,
+            footnote_references: Vec::new(),
+            toc,
+            heading_numbers,
+        }
+    }
+
+    fn extract_title(statements: &mut Vec<Statement>) -> Option<String> {
+        let index = statements
+            .iter()
+            .position(|statement| matches!(statement, Statement::Heading(1, _, _)))?;
+
+        match statements.remove(index) {
+            Statement::Heading(_, expression, _) => {
+                Some(expression.plain_text().trim().to_string())
+            }
+            _ => None,
+        }
+    }
+
+    fn assign_heading_ids(statements: &mut [Statement], heading_ids: bool) {
+        let mut slugger = Slugger::new();
+
+        toc::for_each_heading(statements, &mut |_, _, attributes| {
+            if let Some(id) = &attributes.id {
+                slugger.reserve(id);
+            }
+        });
+
+        if heading_ids {
+            toc::for_each_heading(statements, &mut |_, expression, attributes| {
+                if attributes.id.is_none() {
+                    let slug = slugger.slug(expression.plain_text().trim());
+                    if !slug.is_empty() {
+                        attributes.id = Some(slug);
+                    }
+                }
+            });
+        }
+    }
 
-        Evaluator { statements }
+    fn number_headings(statements: &mut [Statement], options: &Options) -> HashMap<String, String> {
+        let mut numbers = HashMap::new();
+        let mut counters = [0; 6];
+        let start = options.heading_numbers_start();
+
+        for statement in statements {
+            let Statement::Heading(level, _, attributes) = statement else {
+                continue;
+            };
+            if *level < start || attributes.has_class("unnumbered") {
+                continue;
+            }
+
+            let depth = *level - start;
+            counters[depth] += 1;
+            counters[depth + 1..].fill(0);
+
+            let number = counters[..=depth]
+                .iter()
+                .map(|counter| counter.to_string())
+                .collect::<Vec<_>>()
+                .join(&options.heading_numbers_separator);
+            if let Some(id) = &attributes.id {
+                numbers.insert(id.clone(), number.clone());
+            }
+            attributes.number = Some(number);
+        }
+
+        numbers
+    }
+
+    pub fn front_matter(&self) -> &FrontMatter {
+        &self.front_matter
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    pub fn table_of_contents(&self) -> &[TocEntry] {
+        &self.toc
     }
 
     pub fn evaluate(self) -> String {
-        let mut output = String::new();
-        let mut iterator = self.statements.into_iter();
+        self.evaluate_document().html
+    }
 
-        while let Some(statement) = iterator.by_ref().next() {
-            let evaluated = Self::evaluate_statement(statement, &mut iterator);
-            output += &evaluated;
+    pub fn evaluate_document(mut self) -> Document {
+        let mut html = String::new();
+        let Ok(()) = self.stream(&mut |block| {
+            html.push_str(block);
+            Ok::<(), Infallible>(())
+        });
+
+        Document {
+            front_matter: self.front_matter,
+            title: self.title,
+            html,
         }
+    }
+
+    pub fn render_to(mut self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.stream(&mut |block| writer.write_all(block.as_bytes()))
+    }
+
+    pub fn write_to(mut self, output: &mut impl fmt::Write) -> fmt::Result {
+        self.stream(&mut |block| output.write_str(block))
+    }
+
+    fn stream<E>(&mut self, write: &mut Sink<E>) -> Result<(), E> {
+        let statements = std::mem::take(&mut self.statements);
+        self.write_statements(statements, write)?;
+
+        write(&self.evaluate_footnotes())
+    }
+
+    pub fn evaluate_standalone(mut self, template: &Template) -> String {
+        let mut html = String::new();
+        let Ok(()) = self.stream_standalone(template, &mut |text| {
+            html.push_str(text);
+            Ok::<(), Infallible>(())
+        });
+
+        html
+    }
+
+    pub fn render_standalone_to(
+        mut self,
+        template: &Template,
+        writer: &mut impl io::Write,
+    ) -> io::Result<()> {
+        self.stream_standalone(template, &mut |text| writer.write_all(text.as_bytes()))
+    }
+
+    fn stream_standalone<E>(&mut self, template: &Template, write: &mut Sink<E>) -> Result<(), E> {
+        let toc = self.evaluate_toc(&self.toc, false);
+        let stylesheets: String = self
+            .options
+            .stylesheets
+            .iter()
+            .map(|href| format!("<link rel=\"stylesheet\" href=\"{}\">", escape(href)))
+            .collect();
+        let theme = if self.options.inline_theme {
+            format!("<style>\n{}</style>", template::THEME)
+        } else {
+            String::new()
+        };
+
+        let mut values: HashMap<String, String> = self
+            .front_matter
+            .iter()
+            .map(|(key, value)| (key.clone(), escape(&value.to_string())))
+            .collect();
+        values.insert(
+            "title".to_string(),
+            escape(self.title.as_deref().unwrap_or_default()),
+        );
+        values.insert("toc".to_string(), toc);
+        values.insert("stylesheets".to_string(), stylesheets);
+        values.insert("theme".to_string(), theme);
+
+        let mut streamed = false;
+        template.stream(write, &mut |key, write| match key {
+            "content" if !streamed => {
+                streamed = true;
+                self.stream(write)
+            }
+            "content" => Ok(()),
+            key => values.get(key).map_or(Ok(()), |value| write(value)),
+        })
+    }
+
+    fn evaluate_statements(&mut self, statements: Vec<Statement>) -> String {
+        let mut output = String::new();
+        let Ok(()) = self.write_statements(statements, &mut |text| {
+            output.push_str(text);
+            Ok::<(), Infallible>(())
+        });
 
         output
     }
 
+    fn write_statements<E>(
+        &mut self,
+        statements: Vec<Statement>,
+        write: &mut Sink<E>,
+    ) -> Result<(), E> {
+        let mut iterator = statements.into_iter().peekable();
+
+        while let Some(statement) = iterator.next() {
+            self.write_statement(statement, &mut iterator, write)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_statement<E>(
+        &mut self,
+        statement: Statement,
+        iterator: &mut Peekable<IntoIter<Statement>>,
+        write: &mut Sink<E>,
+    ) -> Result<(), E> {
+        match statement {
+            Statement::UnorderedListItem(bullet, ..) => {
+                let mut items = vec![statement];
+                while let Some(item) = iterator.next_if(|statement| {
+                    matches!(statement, Statement::UnorderedListItem(next, ..) if *next == bullet)
+                }) {
+                    items.push(item);
+                }
+
+                self.write_list(false, items, write)
+            }
+            Statement::OrderedListItem(1, ..) => {
+                let mut items = vec![statement];
+                let mut prev_number = 1;
+                while let Some(item) = iterator.next_if(|statement| {
+                    matches!(
+                        statement,
+                        Statement::OrderedListItem(number, ..) if *number == prev_number + 1
+                    )
+                }) {
+                    prev_number += 1;
+                    items.push(item);
+                }
+
+                self.write_list(true, items, write)
+            }
+            Statement::Table(alignments, header, rows) => {
+                let head = self.evaluate_table_row(true, &alignments, header);
+                let body = !rows.is_empty();
+                write(
+                    &self
+                        .options
+                        .renderer
+                        .table_start(&self.options.classes, &head, body),
+                )?;
+                for row in rows {
+                    write(&self.evaluate_table_row(false, &alignments, row))?;
+                }
+
+                write(&self.options.renderer.table_end(&self.options.classes, body))
+            }
+            statement => {
+                let html = self.evaluate_statement(statement, iterator);
+                write(&html)
+            }
+        }
+    }
+
+    fn evaluate_tight(&mut self, mut statements: Vec<Statement>) -> String {
+        match statements.pop() {
+            Some(Statement::Plain(expression)) if statements.is_empty() => {
+                self.evaluate_expression(expression)
+            }
+            Some(statement) => {
+                statements.push(statement);
+                self.evaluate_statements(statements)
+            }
+            None => String::new(),
+        }
+    }
+
+    fn evaluate_footnotes(&mut self) -> String {
+        let mut items = String::new();
+        let mut index = 0;
+
+        while let Some((label, _)) = self.footnote_references.get(index) {
+            let label = label.clone();
+            let statements = self.footnote_definitions.remove(&label).unwrap_or_default();
+            let contents = self.evaluate_statements(statements);
+
+            let mut backrefs = String::new();
+            for reference in 1..=self.footnote_references[index].1 {
+                backrefs += &self.options.renderer.footnote_backref(
+                    &self.options.classes,
+                    &Self::footnote_reference_id(&label, reference),
+                );
+            }
+
+            items += &self.options.renderer.footnote(
+                &self.options.classes,
+                &label,
+                &contents,
+                &backrefs,
+            );
+            index += 1;
+        }
+
+        if items.is_empty() {
+            items
+        } else {
+            self.options
+                .renderer
+                .footnotes(&self.options.classes, &items)
+        }
+    }
+
+    fn footnote_reference_id(label: &str, reference: usize) -> String {
+        if reference == 1 {
+            format!("fnref-{}", label)
+        } else {
+            format!("fnref-{}-{}", label, reference)
+        }
+    }
+
     pub fn evaluate_statement(
+        &mut self,
         statement: Statement,
-        iterator: &mut dyn Iterator<Item = Statement>,
+        iterator: &mut Peekable<IntoIter<Statement>>,
     ) -> String {
         match statement {
-            Statement::Heading(count, expression) => format!(
-                "<h{}>{}</h{}>",
-                count,
-                Self::evaluate_expression(expression),
-                count
-            ),
-            Statement::UnorderedListItem(expression) => {
-                let mut items = format!("<li>{}</li>", Self::evaluate_expression(expression));
+            Statement::Heading(count, expression, attributes) => {
+                let anchor = attributes.id.clone();
+                self.evaluate_heading(count, expression, attributes, anchor.as_deref())
+            }
+            Statement::OrderedListItem(number, task, expression, children) if number != 1 => {
+                format!(
+                    "{}. {}{}{}",
+                    number,
+                    Self::task_marker(&task),
+                    self.evaluate_statement(Statement::Plain(expression), iterator),
+                    self.evaluate_statements(children)
+                )
+            }
+            statement @ (Statement::UnorderedListItem(..)
+            | Statement::OrderedListItem(..)
+            | Statement::Table(..)) => {
+                let mut html = String::new();
+                let Ok(()) = self.write_statement(statement, iterator, &mut |text| {
+                    html.push_str(text);
+                    Ok::<(), Infallible>(())
+                });
 
-                while let Some(statement) = iterator.next() {
-                    match statement {
-                        Statement::UnorderedListItem(expression) => items.push_str(&format!(
-                            "<li>{}</li>",
-                            Self::evaluate_expression(expression)
-                        )),
-                        statement => {
-                            return format!(
-                                "<ul>{}</ul>{}",
-                                items,
-                                Self::evaluate_statement(statement, iterator),
-                            )
-                        }
+                html
+            }
+
+            Statement::CodeBlock(code) => self
+                .options
+                .renderer
+                .code_block(&self.options.classes, &code),
+
+            Statement::DefinitionList(items) => {
+                let mut output = String::new();
+                for (terms, definitions) in items {
+                    for term in terms {
+                        let contents = self.evaluate_expression(term);
+                        output += &self
+                            .options
+                            .renderer
+                            .definition_term(&self.options.classes, &contents);
+                    }
+                    for definition in definitions {
+                        let contents = self.evaluate_tight(definition);
+                        output += &self
+                            .options
+                            .renderer
+                            .definition_description(&self.options.classes, &contents);
                     }
                 }
 
-                format!("<ul>{}</ul>", items)
+                self.options
+                    .renderer
+                    .definition_list(&self.options.classes, &output)
             }
-            Statement::OrderedListItem(number, expression) => {
-                let mut items;
-                let mut prev_number = number;
 
-                if number != 1 {
-                    return format!(
-                        "{}. {}",
-                        number,
-                        Self::evaluate_statement(Statement::Plain(expression), iterator)
-                    );
-                } else {
-                    items = format!("<li>{}</li>", Self::evaluate_expression(expression))
+            Statement::BlockQuote(statements) => {
+                let contents = self.evaluate_statements(statements);
+                self.options
+                    .renderer
+                    .blockquote(&self.options.classes, &contents)
+            }
+
+            Statement::Alert(kind, statements) => {
+                let contents = self.evaluate_statements(statements);
+                let renderer = &self.options.renderer;
+                let classes = &self.options.classes;
+
+                let name = kind.to_lowercase();
+
+                match self.options.alerts.get(&name) {
+                    Some(title) => renderer.alert(classes, &name, title, &contents),
+                    None => renderer.blockquote(
+                        classes,
+                        &(renderer.paragraph(classes, &renderer.text(&format!("[!{}]", kind)))
+                            + &contents),
+                    ),
                 }
+            }
 
-                while let Some(statement) = iterator.next() {
-                    match statement {
-                        Statement::OrderedListItem(number, expression) => {
-                            if number != prev_number + 1 {
-                                return format!(
-                                    "<ol>{}</ol>{}. {}",
-                                    items,
-                                    number,
-                                    Self::evaluate_statement(
-                                        Statement::Plain(expression),
-                                        iterator
-                                    ),
-                                );
-                            }
-                            items.push_str(&format!(
-                                "<li>{}</li>",
-                                Self::evaluate_expression(expression)
-                            ));
-                            prev_number = number;
-                        }
-                        statement => {
-                            return format!(
-                                "<ol>{}</ol>{}",
-                                items,
-                                Self::evaluate_statement(statement, iterator),
-                            )
-                        }
+            Statement::Container(kind, title, attributes, statements) => {
+                let attributes = self.safe_attributes(attributes);
+                let title = title.map(|title| self.evaluate_expression(title));
+                let contents = self.evaluate_statements(statements);
+                let renderer = &self.options.renderer;
+                let classes = &self.options.classes;
+
+                match self.options.containers.get(&kind) {
+                    Some(render) => render(&kind, title.as_deref(), &contents),
+                    None if kind == "details" || kind == "details+" => renderer.details(
+                        classes,
+                        kind == "details+",
+                        title.as_deref().unwrap_or("Details"),
+                        &attributes,
+                        &contents,
+                    ),
+                    None => {
+                        renderer.container(classes, &kind, title.as_deref(), &attributes, &contents)
                     }
                 }
-
-                format!("<ul>{}</ul>", items)
             }
 
-            Statement::TaskListItem(checked, expression) => {
-                fn format_list_item(checked: bool, contents: String) -> String {
-                    let checked = if checked { "checked " } else { "" };
-                    format!(
-                        "<li><input type=\"checkbox\" {}disabled> {}</li>",
-                        checked, contents
-                    )
-                }
-                let mut items = format_list_item(checked, Self::evaluate_expression(expression));
+            Statement::Html(html) => match self.options.raw_html {
+                RawHtml::Allow => html,
+                RawHtml::Escape => self
+                    .options
+                    .renderer
+                    .paragraph(&self.options.classes, &escape(&html)),
+                RawHtml::Strip => String::new(),
+            },
+
+            Statement::Section(id, statements) => {
+                let mut output = String::new();
+                let mut iterator = statements.into_iter().peekable();
 
                 while let Some(statement) = iterator.next() {
-                    match statement {
-                        Statement::TaskListItem(checked, expression) => items.push_str(
-                            &format_list_item(checked, Self::evaluate_expression(expression)),
-                        ),
-                        statement => {
-                            return format!(
-                                "<ul style=\"list-style-type: none\">{}</ul>{}",
-                                items,
-                                Self::evaluate_statement(statement, iterator),
-                            )
+                    output += &match statement {
+                        Statement::Heading(count, expression, attributes) => {
+                            self.evaluate_heading(count, expression, attributes, id.as_deref())
                         }
-                    }
+                        statement => self.evaluate_statement(statement, &mut iterator),
+                    };
                 }
 
-                format!("<ul style=\"list-style-type: none\">{}</ul>", items)
+                let attributes = Attributes {
+                    id,
+                    ..Attributes::default()
+                };
+                self.options
+                    .renderer
+                    .section(&self.options.classes, &attributes, &output)
             }
 
+            Statement::TableOfContents => self.evaluate_toc(&self.toc, false),
+
+            Statement::FootnoteDefinition(_, _) => String::new(),
+
             Statement::Plain(expression) => {
-                format!("<p>{}</p>", Self::evaluate_expression(expression))
+                let contents = self.evaluate_expression(expression);
+                self.options
+                    .renderer
+                    .paragraph(&self.options.classes, &contents)
+            }
+        }
+    }
+
+    fn evaluate_heading(
+        &mut self,
+        count: usize,
+        expression: Expression,
+        attributes: Attributes,
+        anchor: Option<&str>,
+    ) -> String {
+        let mut contents = self.evaluate_expression(expression);
+        if let Some(number) = &attributes.number {
+            contents = self
+                .options
+                .renderer
+                .heading_number(&self.options.classes, number)
+                + &contents;
+        }
+
+        let attributes = self.safe_attributes(attributes);
+        let permalink = anchor.filter(|_| self.options.heading_permalinks);
+        self.options.renderer.heading(
+            &self.options.classes,
+            count,
+            &attributes,
+            &contents,
+            permalink,
+        )
+    }
+
+    fn safe_attributes(&self, mut attributes: Attributes) -> Attributes {
+        if !matches!(self.options.raw_html, RawHtml::Allow) {
+            attributes.retain_safe();
+        }
+
+        attributes
+    }
+
+    fn write_list<E>(
+        &mut self,
+        ordered: bool,
+        items: Vec<Statement>,
+        write: &mut Sink<E>,
+    ) -> Result<(), E> {
+        let tasks = items.iter().any(|item| {
+            matches!(
+                item,
+                Statement::OrderedListItem(_, Some(_), ..)
+                    | Statement::UnorderedListItem(_, Some(_), ..)
+            )
+        });
+        write(
+            &self
+                .options
+                .renderer
+                .list_start(&self.options.classes, ordered, tasks),
+        )?;
+
+        for item in items {
+            if let Statement::OrderedListItem(_, task, expression, children)
+            | Statement::UnorderedListItem(_, task, expression, children) = item
+            {
+                let renderer = &self.options.renderer;
+                let classes = &self.options.classes;
+                let start = match task {
+                    Some(task) => {
+                        let line = Some(task.line).filter(|_| self.options.interactive_task_lists);
+                        renderer.task_list_item_start(classes, task.checked, line)
+                    }
+                    None => renderer.list_item_start(classes),
+                };
+                write(&start)?;
+                write(&self.evaluate_expression(expression))?;
+                self.write_statements(children, write)?;
+                write(&self.options.renderer.list_item_end(&self.options.classes))?;
+            }
+        }
+
+        write(
+            &self
+                .options
+                .renderer
+                .list_end(&self.options.classes, ordered),
+        )
+    }
+
+    fn evaluate_table_row(
+        &mut self,
+        header: bool,
+        alignments: &[Alignment],
+        row: Vec<Expression>,
+    ) -> String {
+        let mut cells = String::new();
+        for (alignment, cell) in alignments.iter().zip(row) {
+            let contents = self.evaluate_expression(cell);
+            cells += &self.options.renderer.table_cell(
+                &self.options.classes,
+                header,
+                alignment,
+                &contents,
+            );
+        }
+
+        self.options
+            .renderer
+            .table_row(&self.options.classes, &cells)
+    }
+
+    fn task_marker(task: &Option<Task>) -> &'static str {
+        match task {
+            Some(Task { checked: true, .. }) => "[x] ",
+            Some(Task { checked: false, .. }) => "[ ] ",
+            None => "",
+        }
+    }
+
+    fn evaluate_toc(&self, entries: &[TocEntry], nested: bool) -> String {
+        if entries.is_empty() {
+            return String::new();
+        }
+
+        let renderer = &self.options.renderer;
+        let classes = &self.options.classes;
+        let mut items = String::new();
+        for entry in entries {
+            let mut contents = escape(&entry.text);
+            if let Some(number) = &entry.number {
+                contents = renderer.heading_number(classes, number) + &contents;
             }
+            let href = Some(entry.slug.as_str()).filter(|slug| !slug.is_empty());
+            let children = self.evaluate_toc(&entry.children, true);
+
+            items += &renderer.toc_item(classes, href, &contents, &children);
         }
+
+        renderer.toc(classes, nested, &items)
     }
 
-    pub fn evaluate_expression(expression: Expression) -> String {
+    pub fn evaluate_expression(&mut self, expression: Expression) -> String {
         match expression {
-            Expression::Text(text) => text,
+            Expression::Text(text) => self.options.renderer.text(&text),
+            Expression::Html(html) => match self.options.raw_html {
+                RawHtml::Allow => html,
+                RawHtml::Escape => escape(&html),
+                RawHtml::Strip => String::new(),
+            },
             Expression::Bold(expression) => {
-                format!(
-                    "<strong>{}</strong>",
-                    Self::evaluate_expression(*expression)
-                )
+                let contents = self.evaluate_expression(*expression);
+                self.options
+                    .renderer
+                    .strong(&self.options.classes, &contents)
             }
             Expression::Italic(expression) => {
-                format!("<i>{}</i>", Self::evaluate_expression(*expression))
+                let contents = self.evaluate_expression(*expression);
+                self.options
+                    .renderer
+                    .emphasis(&self.options.classes, &contents)
             }
             Expression::BoldItalic(expression) => {
-                format!(
-                    "<strong><i>{}</i></strong>",
-                    Self::evaluate_expression(*expression)
-                )
+                let contents = self.evaluate_expression(*expression);
+                self.options
+                    .renderer
+                    .strong_emphasis(&self.options.classes, &contents)
             }
-            Expression::Link(title, link) => {
-                format!(
-                    "<a href=\"{}\">{}</a>",
-                    Self::evaluate_expression(*link),
-                    Self::evaluate_expression(*title)
+            Expression::Link(title, link, attributes) => {
+                let number = link
+                    .plain_text()
+                    .strip_prefix('#')
+                    .and_then(|id| self.heading_numbers.get(id))
+                    .cloned();
+                let contents = match number {
+                    Some(number) if title.plain_text().is_empty() => escape(&number),
+                    _ => self.evaluate_expression(*title),
+                };
+                let href = self.evaluate_expression(*link);
+                let attributes = self.safe_attributes(attributes);
+
+                self.options
+                    .renderer
+                    .link(&self.options.classes, &href, &attributes, &contents)
+            }
+            Expression::FootnoteReference(label) => {
+                let position = self
+                    .footnote_references
+                    .iter()
+                    .position(|(other, _)| *other == label);
+                let index = match position {
+                    Some(index) => index,
+                    None if self.footnote_definitions.contains_key(&label) => {
+                        self.footnote_references.push((label.clone(), 0));
+                        self.footnote_references.len() - 1
+                    }
+                    None => return self.options.renderer.text(&format!("[^{}]", label)),
+                };
+                self.footnote_references[index].1 += 1;
+
+                self.options.renderer.footnote_reference(
+                    &self.options.classes,
+                    &label,
+                    &Self::footnote_reference_id(&label, self.footnote_references[index].1),
+                    index + 1,
                 )
             }
             Expression::Vec(expressions) => {
                 let mut output = String::new();
 
                 for expression in expressions {
-                    output += &Self::evaluate_expression(expression);
+                    output += &self.evaluate_expression(expression);
                 }
 
                 output
@@ -188,7 +777,41 @@ mod tests {
         ));
         let output = evaluator.evaluate();
 
-        assert_eq!(output, "<p>Hello, World!</p><h2>Hi there</h2><p>#Hi</p>");
+        assert_eq!(
+            output,
+            "<p>Hello, World!</p><h2 id=\"hi-there\">Hi there</h2><p>#Hi</p>"
+        );
+    }
+
+    #[test]
+    fn evaluates_atx_heading() {
+        let evaluator = Evaluator::new(String::from(
+            "## **Hi** there ##
+#
+####### too deep",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<h2 id=\"hi-there\"><strong>Hi</strong> there</h2><h1></h1><p>####### too deep</p>"
+        );
+    }
+
+    #[test]
+    fn evaluates_setext_heading() {
+        let evaluator = Evaluator::new(String::from(
+            "Title
+=====
+*Sub*title
+---",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<h1 id=\"title\">Title</h1><h2 id=\"subtitle\"><i>Sub</i>title</h2>"
+        );
     }
 
     #[test]
@@ -201,7 +824,7 @@ mod tests {
 
         assert_eq!(
             output,
-            "<p><i>Hi</i> <strong>there</strong></p><h1><i>Hi there</i>*</h1>"
+            "<p><i>Hi</i> <strong>there</strong></p><h1 id=\"hi-there\"><i>Hi there</i>*</h1>"
         )
     }
 
@@ -220,10 +843,681 @@ mod tests {
 
         assert_eq!(
             output,
-            "<ul><li>Hi</li><li>there</li><li># fake heading</li></ul><h1>heading</h1><ol><li>first</li><li>second</li></ol><p>4.fourth</p>"
+            "<ul><li>Hi</li><li>there</li><li># fake heading</li></ul><h1 id=\"heading\">heading</h1><ol><li>first</li><li>second</li></ol><p>4.fourth</p>"
         )
     }
 
+    #[test]
+    fn closes_ordered_list_at_end_of_input() {
+        let evaluator = Evaluator::new(String::from(
+            "1. first
+2. second",
+        ));
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<ol><li>first</li><li>second</li></ol>"
+        )
+    }
+
+    #[test]
+    fn evaluates_code_block() {
+        let evaluator = Evaluator::new(String::from(
+            "    <b>**not bold**</b>
+    a & b",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<pre><code>&lt;b&gt;**not bold**&lt;/b&gt;\na &amp; b\n</code></pre>"
+        )
+    }
+
+    #[test]
+    fn evaluates_crlf_input() {
+        let evaluator = Evaluator::new(String::from(
+            "\u{feff}# Title\r\n[link](https://example.test)\r\n- [x] done\r\n",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<h1 id=\"title\">Title</h1><p><a href=\"https://example.test\">link</a></p><ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked disabled> done</li></ul><p></p>"
+        )
+    }
+
+    #[test]
+    fn evaluates_table() {
+        let evaluator = Evaluator::new(String::from(
+            "| Name | Value | Note |
+|:-----|:-----:|-----:|
+| *a* | 1 |
+| b \\| c | 2 | x | extra",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<table><thead><tr><th align=\"left\">Name</th><th align=\"center\">Value</th><th align=\"right\">Note</th></tr></thead><tbody><tr><td align=\"left\"><i>a</i></td><td align=\"center\">1</td><td align=\"right\"></td></tr><tr><td align=\"left\">b | c</td><td align=\"center\">2</td><td align=\"right\">x</td></tr></tbody></table>"
+        )
+    }
+
+    #[test]
+    fn evaluates_footnotes() {
+        let evaluator = Evaluator::new(String::from(
+            "B[^b] A[^a] B[^b] missing[^c]
+[^a]: Alpha
+[^b]: Beta
+
+    More beta
+[^unused]: Never shown",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<p>B<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b\">1</a></sup> A<sup class=\"footnote-ref\"><a href=\"#fn-a\" id=\"fnref-a\">2</a></sup> B<sup class=\"footnote-ref\"><a href=\"#fn-b\" id=\"fnref-b-2\">1</a></sup> missing[^c]</p>\
+<section class=\"footnotes\"><ol>\
+<li id=\"fn-b\"><p>Beta</p><p></p><p>More beta</p> <a href=\"#fnref-b\" class=\"footnote-backref\">↩</a> <a href=\"#fnref-b-2\" class=\"footnote-backref\">↩</a></li>\
+<li id=\"fn-a\"><p>Alpha</p> <a href=\"#fnref-a\" class=\"footnote-backref\">↩</a></li>\
+</ol></section>"
+        )
+    }
+
+    #[test]
+    fn escapes_undefined_footnote_labels() {
+        let options = Options {
+            raw_html: RawHtml::Escape,
+            ..Options::default()
+        };
+        let evaluator =
+            Evaluator::with_options(String::from("a[^<script>alert(1)</script>]"), options);
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<p>a[^&lt;script&gt;alert(1)&lt;/script&gt;]</p>"
+        );
+    }
+
+    #[test]
+    fn evaluates_definition_list() {
+        let evaluator = Evaluator::new(String::from(
+            "**Term**
+: First
+: Second
+
+    - with a list
+Other
+: Third",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<dl><dt><strong>Term</strong></dt><dd>First</dd><dd><p>Second</p><p></p><ul><li>with a list</li></ul></dd><dt>Other</dt><dd>Third</dd></dl>"
+        )
+    }
+
+    #[test]
+    fn evaluates_blockquote() {
+        let evaluator = Evaluator::new(String::from(
+            "> Quoted
+> - item",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<blockquote><p>Quoted</p><ul><li>item</li></ul></blockquote>"
+        )
+    }
+
+    #[test]
+    fn evaluates_alerts() {
+        let mut options = Options::default();
+        options
+            .alerts
+            .insert("todo".to_string(), "To <do>".to_string());
+
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "> [!NOTE]
+> Read this
+
+> [!TODO]
+> Later
+
+> [!Custom]
+> Plain",
+            ),
+            options,
+        );
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<div class=\"markdown-alert markdown-alert-note\"><p class=\"markdown-alert-title\">Note</p><p>Read this</p></div><p></p>\
+<div class=\"markdown-alert markdown-alert-todo\"><p class=\"markdown-alert-title\">To &lt;do&gt;</p><p>Later</p></div><p></p>\
+<blockquote><p>[!Custom]</p><p>Plain</p></blockquote>"
+        )
+    }
+
+    #[test]
+    fn evaluates_containers() {
+        let mut options = Options::default();
+        options.containers.insert(
+            "spoiler".to_string(),
+            Box::new(|_, title, contents| {
+                format!(
+                    "<details><summary>{}</summary>{}</details>",
+                    title.unwrap_or("Spoiler"),
+                    contents
+                )
+            }),
+        );
+
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "::: warning Be **careful**
+# Heading
+:::
+::: spoiler
+hidden
+:::",
+            ),
+            options,
+        );
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<div class=\"warning\"><p class=\"container-title\">Be <strong>careful</strong></p><h1 id=\"heading\">Heading</h1></div><details><summary>Spoiler</summary><p>hidden</p></details>"
+        )
+    }
+
+    #[test]
+    fn evaluates_details() {
+        let evaluator = Evaluator::new(String::from(
+            "::: details Show *log*
+    error: failed
+:::
+::: details+
+Expanded
+:::",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<details><summary>Show <i>log</i></summary><pre><code>error: failed\n</code></pre></details><details open><summary>Details</summary><p>Expanded</p></details>"
+        )
+    }
+
+    #[test]
+    fn evaluates_front_matter() {
+        let evaluator = Evaluator::new(String::from(
+            "---
+title: Guide
+tags: [docs]
+---
+# Guide",
+        ));
+        assert_eq!(evaluator.front_matter().title().as_deref(), Some("Guide"));
+
+        let document = evaluator.evaluate_document();
+
+        assert_eq!(document.html, "<h1 id=\"guide\">Guide</h1>");
+        assert_eq!(document.front_matter.tags(), vec!["docs"]);
+    }
+
+    #[test]
+    fn evaluates_raw_html() {
+        let input = "<div>
+**raw**
+</div>
+
+a <b>bold</b> & b &amp; 1 < 2";
+
+        let output = Evaluator::new(input.to_string()).evaluate();
+        assert_eq!(
+            output,
+            "<div>\n**raw**\n</div><p></p><p>a <b>bold</b> &amp; b &amp; 1 &lt; 2</p>"
+        );
+
+        let options = Options {
+            raw_html: RawHtml::Escape,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options(input.to_string(), options).evaluate();
+        assert_eq!(
+            output,
+            "<p>&lt;div&gt;\n**raw**\n&lt;/div&gt;</p><p></p><p>a &lt;b&gt;bold&lt;/b&gt; &amp; b &amp; 1 &lt; 2</p>"
+        );
+
+        let options = Options {
+            raw_html: RawHtml::Strip,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options(input.to_string(), options).evaluate();
+        assert_eq!(output, "<p></p><p>a bold &amp; b &amp; 1 &lt; 2</p>");
+    }
+
+    #[test]
+    fn evaluates_heading_ids() {
+        let evaluator = Evaluator::new(String::from(
+            "# Getting *Started*!
+## Getting Started
+### [Links](https://example.test) & more",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<h1 id=\"getting-started\">Getting <i>Started</i>!</h1><h2 id=\"getting-started-1\">Getting Started</h2><h3 id=\"links--more\"><a href=\"https://example.test\">Links</a> &amp; more</h3>"
+        )
+    }
+
+    #[test]
+    fn evaluates_heading_permalinks() {
+        let options = Options {
+            heading_permalinks: true,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(String::from("## Install"), options);
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<h2 id=\"install\">Install <a class=\"anchor\" href=\"#install\" aria-hidden=\"true\">#</a></h2>"
+        );
+
+        let options = Options {
+            heading_ids: false,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(String::from("## Install"), options);
+
+        assert_eq!(evaluator.evaluate(), "<h2>Install</h2>");
+    }
+
+    #[test]
+    fn evaluates_attributes() {
+        let evaluator = Evaluator::new(String::from(
+            "## Install {#install .important data-x=\"a b\"}
+# Install
+::: note {#intro .wide}
+[Docs](https://example.test){.button rel=nofollow}
+:::",
+        ));
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<h2 id=\"install\" class=\"important\" data-x=\"a b\">Install</h2><h1 id=\"install-1\">Install</h1>\
+<div id=\"intro\" class=\"note wide\"><p><a href=\"https://example.test\" class=\"button\" rel=\"nofollow\">Docs</a></p></div>"
+        )
+    }
+
+    #[test]
+    fn drops_unsafe_attributes_without_raw_html() {
+        let input = "# Hi {onclick=x STYLE=\"color: red\" data-x=1}
+[x](y){onmouseover=x .button}";
+        let options = Options {
+            raw_html: RawHtml::Escape,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            Evaluator::with_options(String::from(input), options).evaluate(),
+            "<h1 id=\"hi\" data-x=\"1\">Hi</h1><p><a href=\"y\" class=\"button\">x</a></p>"
+        );
+        assert_eq!(
+            Evaluator::new(String::from(input)).evaluate(),
+            "<h1 id=\"hi\" onclick=\"x\" STYLE=\"color: red\" data-x=\"1\">Hi</h1><p><a href=\"y\" class=\"button\" onmouseover=\"x\">x</a></p>"
+        );
+    }
+
+    #[test]
+    fn evaluates_table_of_contents() {
+        let input = "# Guide
+[TOC]
+## Install & *Setup*
+### Linux
+## Usage {#use}
+#### Deep";
+
+        let output = Evaluator::new(input.to_string()).evaluate();
+        assert_eq!(
+            output,
+            "<h1 id=\"guide\">Guide</h1>\
+<ul class=\"toc\"><li><a href=\"#guide\">Guide</a><ul>\
+<li><a href=\"#install--setup\">Install &amp; Setup</a><ul><li><a href=\"#linux\">Linux</a></li></ul></li>\
+<li><a href=\"#use\">Usage</a><ul><li><a href=\"#deep\">Deep</a></li></ul></li>\
+</ul></li></ul>\
+<h2 id=\"install--setup\">Install &amp; <i>Setup</i></h2><h3 id=\"linux\">Linux</h3><h2 id=\"use\">Usage</h2><h4 id=\"deep\">Deep</h4>"
+        );
+
+        let options = Options {
+            toc_min_depth: 2,
+            toc_max_depth: 2,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(input.to_string(), options);
+        assert_eq!(evaluator.table_of_contents().len(), 2);
+        assert!(evaluator.evaluate().contains(
+            "<ul class=\"toc\"><li><a href=\"#install--setup\">Install &amp; Setup</a></li><li><a href=\"#use\">Usage</a></li></ul>"
+        ));
+    }
+
+    #[test]
+    fn evaluates_heading_numbers() {
+        let input = "# Title
+[TOC]
+## Scope
+## Design {#design}
+#### Data
+### Storage
+## Notes {-}
+## Summary
+See [](#design) and [the storage part](#storage).";
+
+        let options = Options {
+            heading_numbers: true,
+            heading_numbers_start: 2,
+            toc_min_depth: 2,
+            toc_max_depth: 3,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options(input.to_string(), options).evaluate();
+
+        assert_eq!(
+            output,
+            "<h1 id=\"title\">Title</h1>\
+<ul class=\"toc\"><li><a href=\"#scope\"><span class=\"heading-number\">1</span> Scope</a></li>\
+<li><a href=\"#design\"><span class=\"heading-number\">2</span> Design</a><ul>\
+<li><a href=\"#storage\"><span class=\"heading-number\">2.1</span> Storage</a></li></ul></li>\
+<li><a href=\"#notes\">Notes</a></li>\
+<li><a href=\"#summary\"><span class=\"heading-number\">3</span> Summary</a></li></ul>\
+<h2 id=\"scope\" data-number=\"1\"><span class=\"heading-number\">1</span> Scope</h2>\
+<h2 id=\"design\" data-number=\"2\"><span class=\"heading-number\">2</span> Design</h2>\
+<h4 id=\"data\" data-number=\"2.0.1\"><span class=\"heading-number\">2.0.1</span> Data</h4>\
+<h3 id=\"storage\" data-number=\"2.1\"><span class=\"heading-number\">2.1</span> Storage</h3>\
+<h2 id=\"notes\" class=\"unnumbered\">Notes</h2>\
+<h2 id=\"summary\" data-number=\"3\"><span class=\"heading-number\">3</span> Summary</h2>\
+<p>See <a href=\"#design\">2</a> and <a href=\"#storage\">the storage part</a>.</p>"
+        );
+
+        let options = Options {
+            heading_numbers: true,
+            heading_numbers_separator: "-".to_string(),
+            heading_ids: false,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options("# A\n## B".to_string(), options).evaluate();
+        assert_eq!(
+            output,
+            "<h1 data-number=\"1\"><span class=\"heading-number\">1</span> A</h1><h2 data-number=\"1-1\"><span class=\"heading-number\">1-1</span> B</h2>"
+        );
+
+        let options = Options {
+            heading_numbers: true,
+            heading_numbers_start: 0,
+            heading_ids: false,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options("# A\n###### B".to_string(), options).evaluate();
+        assert_eq!(
+            output,
+            "<h1 data-number=\"1\"><span class=\"heading-number\">1</span> A</h1><h6 data-number=\"1.0.0.0.0.1\"><span class=\"heading-number\">1.0.0.0.0.1</span> B</h6>"
+        );
+
+        let output = Evaluator::new("# A {data-number=9}".to_string()).evaluate();
+        assert_eq!(output, "<h1 id=\"a\" data-number=\"9\">A</h1>");
+    }
+
+    #[test]
+    fn evaluates_sections() {
+        let options = Options {
+            sections: true,
+            heading_permalinks: true,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "Intro
+# Guide
+## Install {.wide}
+- step
+## Usage
+text",
+            ),
+            options,
+        );
+        let output = evaluator.evaluate();
+
+        assert_eq!(
+            output,
+            "<p>Intro</p>\
+<section id=\"guide\"><h1>Guide <a class=\"anchor\" href=\"#guide\" aria-hidden=\"true\">#</a></h1>\
+<section id=\"install\"><h2 class=\"wide\">Install <a class=\"anchor\" href=\"#install\" aria-hidden=\"true\">#</a></h2><ul><li>step</li></ul></section>\
+<section id=\"usage\"><h2>Usage <a class=\"anchor\" href=\"#usage\" aria-hidden=\"true\">#</a></h2><p>text</p></section>\
+</section>"
+        )
+    }
+
+    #[test]
+    fn evaluates_heading_offset() {
+        let input = "# Guide
+[TOC]
+# Install
+##### Deep
+###### Deeper";
+
+        let options = Options {
+            heading_offset: 1,
+            heading_numbers: true,
+            heading_numbers_start: 2,
+            toc_min_depth: 2,
+            toc_max_depth: 2,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options(input.to_string(), options).evaluate();
+        assert_eq!(
+            output,
+            "<h2 id=\"guide\" data-number=\"1\"><span class=\"heading-number\">1</span> Guide</h2>\
+<ul class=\"toc\"><li><a href=\"#guide\"><span class=\"heading-number\">1</span> Guide</a></li><li><a href=\"#install\"><span class=\"heading-number\">2</span> Install</a></li></ul>\
+<h2 id=\"install\" data-number=\"2\"><span class=\"heading-number\">2</span> Install</h2>\
+<h6 id=\"deep\" data-number=\"2.0.0.0.1\"><span class=\"heading-number\">2.0.0.0.1</span> Deep</h6>\
+<h6 id=\"deeper\" data-number=\"2.0.0.0.2\"><span class=\"heading-number\">2.0.0.0.2</span> Deeper</h6>"
+        );
+
+        let options = Options {
+            extract_title: true,
+            ..Options::default()
+        };
+        let document = Evaluator::with_options(input.to_string(), options).evaluate_document();
+        assert_eq!(document.title.as_deref(), Some("Guide"));
+        assert!(document.html.starts_with(
+            "<ul class=\"toc\"><li><a href=\"#install\">Install</a><ul><li><a href=\"#deep\">Deep</a>"
+        ));
+    }
+
+    #[test]
+    fn evaluates_standalone_document() {
+        let input = "---
+title: Guide & Notes
+author: Sam
+---
+# Install";
+
+        let template = Template::new(String::from(
+            "<title>{{ title }}</title><p>{{ author }}</p>{{ toc }}{{ content }}",
+        ));
+        let output = Evaluator::new(input.to_string()).evaluate_standalone(&template);
+        assert_eq!(
+            output,
+            "<title>Guide &amp; Notes</title><p>Sam</p><ul class=\"toc\"><li><a href=\"#install\">Install</a></li></ul><h1 id=\"install\">Install</h1>"
+        );
+
+        let options = Options {
+            stylesheets: vec!["theme.css".to_string()],
+            ..Options::default()
+        };
+        let output = Evaluator::with_options("## First *heading*".to_string(), options)
+            .evaluate_standalone(&Template::default());
+        assert!(output.starts_with("<!DOCTYPE html>"));
+        assert!(output.contains("<meta charset=\"utf-8\">"));
+        assert!(output.contains("<title>First heading</title>"));
+        assert!(output.contains("<link rel=\"stylesheet\" href=\"theme.css\">"));
+        assert!(output.contains("<h2 id=\"first-heading\">First <i>heading</i></h2>"));
+        assert!(output.contains("<style>"));
+
+        let options = Options {
+            inline_theme: false,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options("# Strict".to_string(), options)
+            .evaluate_standalone(&Template::default());
+        assert!(!output.contains("<style"));
+        assert!(!output.contains("style="));
+    }
+
+    #[test]
+    fn streams_output() {
+        let input = "# Title
+- one
+- two
+
+Text[^1]
+
+[^1]: Note";
+
+        let mut bytes = Vec::new();
+        Evaluator::new(String::from(input))
+            .render_to(&mut bytes)
+            .unwrap();
+        let mut output = String::new();
+        Evaluator::new(String::from(input))
+            .write_to(&mut output)
+            .unwrap();
+
+        assert_eq!(String::from_utf8(bytes).unwrap(), output);
+        assert_eq!(output, Evaluator::new(String::from(input)).evaluate());
+    }
+
+    struct FailingWriter {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written.len() + buf.len() > self.limit {
+                return Err(io::Error::other("disk full"));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn propagates_write_errors() {
+        let input = "# Title\n\nFirst\n\nSecond";
+        let mut writer = FailingWriter {
+            written: Vec::new(),
+            limit: 30,
+        };
+        let error = Evaluator::new(String::from(input))
+            .render_to(&mut writer)
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "disk full");
+        assert_eq!(
+            String::from_utf8(writer.written).unwrap(),
+            "<h1 id=\"title\">Title</h1>"
+        );
+
+        let mut writer = FailingWriter {
+            written: Vec::new(),
+            limit: 30,
+        };
+        let template = Template::new(String::from("<main>{{ content }}</main>"));
+        let error = Evaluator::new(String::from(input))
+            .render_standalone_to(&template, &mut writer)
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "disk full");
+        assert_eq!(String::from_utf8(writer.written).unwrap(), "<main>");
+    }
+
+    #[test]
+    fn streams_standalone_document() {
+        let template = Template::default();
+        let mut bytes = Vec::new();
+        Evaluator::new(String::from("# Title\n\nText"))
+            .render_standalone_to(&template, &mut bytes)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            Evaluator::new(String::from("# Title\n\nText")).evaluate_standalone(&template)
+        );
+    }
+
+    #[test]
+    fn renders_content_once_in_templates() {
+        let template = Template::new(String::from("{{ content }}|{{ content }}"));
+        let evaluator = Evaluator::new(String::from("Text[^1]\n\n[^1]: Note"));
+
+        assert_eq!(
+            evaluator.evaluate_standalone(&template),
+            "<p>Text<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\">1</a></sup></p><p></p><section class=\"footnotes\"><ol><li id=\"fn-1\"><p>Note</p> <a href=\"#fnref-1\" class=\"footnote-backref\">↩</a></li></ol></section>|"
+        );
+    }
+
+    struct Chunks(Vec<String>);
+
+    impl fmt::Write for Chunks {
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            self.0.push(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streams_list_items_and_table_rows() {
+        let mut chunks = Chunks(Vec::new());
+        Evaluator::new(String::from("- a\n  - b\n\nx | y\n--- | ---\n1 | 2"))
+            .write_to(&mut chunks)
+            .unwrap();
+
+        assert_eq!(
+            chunks.0,
+            vec![
+                "<ul>",
+                "<li>",
+                "a",
+                "<ul>",
+                "<li>",
+                "b",
+                "</li>",
+                "</ul>",
+                "</li>",
+                "</ul>",
+                "<p></p>",
+                "<table><thead><tr><th>x</th><th>y</th></tr></thead><tbody>",
+                "<tr><td>1</td><td>2</td></tr>",
+                "</tbody></table>",
+                "",
+            ]
+        );
+    }
+
     #[test]
     fn evaluates_link() {
         let evaluator = Evaluator::new(String::from("[Google](https://google.com)"));
@@ -242,7 +1536,82 @@ mod tests {
 
         assert_eq!(
             output,
-            "<ul style=\"list-style-type: none\"><li><input type=\"checkbox\" checked disabled> Hi</li><li><input type=\"checkbox\" disabled> there</li></ul>"
+            "<ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked disabled> Hi</li><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled> there</li></ul>"
         )
     }
+
+    #[test]
+    fn evaluates_mixed_task_list() {
+        let evaluator = Evaluator::new(String::from(
+            "- [X] Hi
+- there
+1. [ ] one",
+        ));
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" checked disabled> Hi</li><li>there</li></ul><ol class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled> one</li></ol>"
+        )
+    }
+
+    #[test]
+    fn evaluates_nested_task_list() {
+        let options = Options {
+            interactive_task_lists: true,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "- [ ] Release
+  - [x] Tag
+    1. [ ] Notes
+- Announce",
+            ),
+            options,
+        );
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" data-line=\"1\"> Release<ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" data-line=\"2\" checked> Tag<ol class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" data-line=\"3\"> Notes</li></ol></li></ul></li><li>Announce</li></ul>"
+        );
+    }
+
+    #[test]
+    fn starts_new_list_when_bullet_changes() {
+        let evaluator = Evaluator::new(String::from(
+            "- one
+- two
+* three
++ four",
+        ));
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<ul><li>one</li><li>two</li></ul><ul><li>three</li></ul><ul><li>four</li></ul>"
+        );
+    }
+
+    #[test]
+    fn evaluates_interactive_task_list() {
+        let options = Options {
+            interactive_task_lists: true,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "---
+title: Chores
+---
+- [x] Dishes
+
+> - [ ] Laundry",
+            ),
+            options,
+        );
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" data-line=\"4\" checked> Dishes</li></ul><p></p><blockquote><ul class=\"contains-task-list\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" data-line=\"6\"> Laundry</li></ul></blockquote>"
+        );
+    }
 }
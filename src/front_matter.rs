@@ -0,0 +1,415 @@
+use crate::lexer::split_lines;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+#[derive(Debug, PartialEq, Default)]
+pub struct FrontMatter {
+    values: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Boolean(bool),
+    Integer(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Integer(integer) => write!(f, "{}", integer),
+            Value::Float(float) => write!(f, "{}", float),
+            Value::String(string) => write!(f, "{}", string),
+            Value::List(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FrontMatter {
+    pub fn extract(input: &str) -> Option<(FrontMatter, &str)> {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let mut lines = split_lines(input);
+
+        let first = lines.next()?;
+        let toml = match first.trim_end() {
+            "---" => false,
+            "+++" => true,
+            _ => return None,
+        };
+
+        let mut offset = first.len();
+        for line in lines {
+            let delimiter = line.trim_end();
+            if (toml && delimiter == "+++") || (!toml && (delimiter == "---" || delimiter == "..."))
+            {
+                let source = &input[first.len()..offset];
+                let values = if toml {
+                    Self::parse_toml(source)
+                } else {
+                    Self::parse_yaml(source)
+                };
+
+                return Some((FrontMatter { values }, &input[offset + line.len()..]));
+            }
+            offset += line.len();
+        }
+
+        None
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.values.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.values.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn title(&self) -> Option<String> {
+        self.scalar("title")
+    }
+
+    pub fn date(&self) -> Option<String> {
+        self.scalar("date")
+    }
+
+    fn scalar(&self, key: &str) -> Option<String> {
+        match self.get(key)? {
+            Value::Null | Value::List(_) => None,
+            value => Some(value.to_string()),
+        }
+    }
+
+    pub fn tags(&self) -> Vec<&str> {
+        match self.get("tags") {
+            Some(Value::List(tags)) => tags.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(tag)) => vec![tag],
+            _ => Vec::new(),
+        }
+    }
+
+    fn parse_yaml(source: &str) -> BTreeMap<String, Value> {
+        let mut values = BTreeMap::new();
+        let mut parents: Vec<(usize, String)> = Vec::new();
+        let mut last_key: Option<String> = None;
+
+        for line in split_lines(source) {
+            let trimmed = Self::strip_comment(line).trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some(item) = trimmed.strip_prefix('-') {
+                let value = last_key.as_ref().and_then(|key| values.get_mut(key));
+                match value {
+                    Some(Value::List(items)) => items.push(Self::parse_value(item.trim())),
+                    Some(value @ Value::Null) => {
+                        *value = Value::List(vec![Self::parse_value(item.trim())])
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            if let Some((key, value)) = trimmed.split_once(':') {
+                let indentation = line.len() - line.trim_start().len();
+                while parents
+                    .last()
+                    .is_some_and(|(parent, _)| *parent >= indentation)
+                {
+                    parents.pop();
+                }
+
+                let key = Self::unquote(key.trim());
+                let key = match parents.last() {
+                    Some((_, parent)) => {
+                        if values.get(parent) == Some(&Value::Null) {
+                            values.remove(parent);
+                        }
+                        format!("{}.{}", parent, key)
+                    }
+                    None => key.to_string(),
+                };
+
+                let value = value.trim();
+                let value = if value.is_empty() {
+                    parents.push((indentation, key.clone()));
+                    Value::Null
+                } else {
+                    Self::parse_value(value)
+                };
+
+                values.insert(key.clone(), value);
+                last_key = Some(key);
+            }
+        }
+
+        values
+    }
+
+    fn parse_toml(source: &str) -> BTreeMap<String, Value> {
+        let mut values = BTreeMap::new();
+        let mut table = String::new();
+
+        for line in split_lines(source) {
+            let trimmed = Self::strip_comment(line).trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = trimmed
+                .strip_prefix('[')
+                .and_then(|name| name.strip_suffix(']'))
+            {
+                table = format!("{}.", name.trim());
+                continue;
+            }
+
+            if let Some((key, value)) = trimmed.split_once('=') {
+                let key = format!("{}{}", table, Self::unquote(key.trim()));
+                values.insert(key, Self::parse_value(value.trim()));
+            }
+        }
+
+        values
+    }
+
+    fn parse_value(text: &str) -> Value {
+        if let Some(items) = text
+            .strip_prefix('[')
+            .and_then(|text| text.strip_suffix(']'))
+        {
+            return Value::List(
+                Self::split_items(items)
+                    .into_iter()
+                    .map(Self::parse_value)
+                    .collect(),
+            );
+        }
+
+        if text.len() >= 2 && (text.starts_with('"') || text.starts_with('\'')) {
+            return Value::String(Self::unquote(text).to_string());
+        }
+
+        match text {
+            "" | "~" | "null" => Value::Null,
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            text => {
+                if let Ok(integer) = text.parse() {
+                    Value::Integer(integer)
+                } else if let Ok(float) = text.parse() {
+                    Value::Float(float)
+                } else {
+                    Value::String(text.to_string())
+                }
+            }
+        }
+    }
+
+    fn split_items(text: &str) -> Vec<&str> {
+        let mut items = Vec::new();
+        let mut quote = None;
+        let mut start = 0;
+
+        for (index, char) in text.char_indices() {
+            match (quote, char) {
+                (None, '"' | '\'') => quote = Some(char),
+                (Some(open), char) if open == char => quote = None,
+                (None, ',') => {
+                    items.push(text[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        items.push(text[start..].trim());
+
+        items.into_iter().filter(|item| !item.is_empty()).collect()
+    }
+
+    fn strip_comment(line: &str) -> &str {
+        let mut quote = None;
+        let mut previous = ' ';
+
+        for (index, char) in line.char_indices() {
+            match (quote, char) {
+                (None, '"' | '\'')
+                    if previous.is_whitespace() || matches!(previous, ':' | '=' | '[' | ',') =>
+                {
+                    quote = Some(char)
+                }
+                (Some(open), char) if open == char => quote = None,
+                (None, '#') if previous.is_whitespace() => return &line[..index],
+                _ => {}
+            }
+            previous = char;
+        }
+
+        line
+    }
+
+    fn unquote(text: &str) -> &str {
+        for quote in ['"', '\''] {
+            if let Some(text) = text
+                .strip_prefix(quote)
+                .and_then(|text| text.strip_suffix(quote))
+            {
+                return text;
+            }
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_yaml() {
+        let (front_matter, body) = FrontMatter::extract(
+            "---
+title: \"Hello: World\"
+date: 2024-05-01
+draft: false
+weight: 3
+tags:
+  - rust
+  - markdown
+aliases: [one, 'two, three']
+---
+# Body",
+        )
+        .unwrap();
+
+        assert_eq!(body, "# Body");
+        assert_eq!(front_matter.title().as_deref(), Some("Hello: World"));
+        assert_eq!(front_matter.date().as_deref(), Some("2024-05-01"));
+        assert_eq!(front_matter.tags(), vec!["rust", "markdown"]);
+        assert_eq!(front_matter.get("draft"), Some(&Value::Boolean(false)));
+        assert_eq!(front_matter.get("weight"), Some(&Value::Integer(3)));
+        assert_eq!(
+            front_matter.get("tags").unwrap().to_string(),
+            "rust, markdown"
+        );
+        assert_eq!(
+            front_matter.get("aliases"),
+            Some(&Value::List(vec![
+                Value::String("one".to_string()),
+                Value::String("two, three".to_string()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn extracts_nested_yaml() {
+        let (front_matter, _) = FrontMatter::extract(
+            "---
+# metadata
+title: Sam's notes # draft title
+author:
+  name: Sam
+  links:
+    site: \"https://example.test/#about\"
+tags:
+  - rust # language
+layout: post
+---
+",
+        )
+        .unwrap();
+
+        assert_eq!(front_matter.title().as_deref(), Some("Sam's notes"));
+        assert_eq!(front_matter.get("author"), None);
+        assert_eq!(
+            front_matter.get("author.name"),
+            Some(&Value::String("Sam".to_string()))
+        );
+        assert_eq!(
+            front_matter.get("author.links.site"),
+            Some(&Value::String("https://example.test/#about".to_string()))
+        );
+        assert_eq!(front_matter.tags(), vec!["rust"]);
+        assert_eq!(
+            front_matter.get("layout"),
+            Some(&Value::String("post".to_string()))
+        );
+    }
+
+    #[test]
+    fn extracts_toml() {
+        let (front_matter, body) = FrontMatter::extract(
+            "\u{feff}+++\r
+title = 'Release notes'\r
+tags = [\"news\"]\r
+ratio = 0.5\r
+[extra]\r
+author = \"Sam\"\r
++++\r
+Text",
+        )
+        .unwrap();
+
+        assert_eq!(body, "Text");
+        assert_eq!(front_matter.title().as_deref(), Some("Release notes"));
+        assert_eq!(front_matter.tags(), vec!["news"]);
+        assert_eq!(front_matter.get("ratio"), Some(&Value::Float(0.5)));
+        assert_eq!(
+            front_matter.get("extra.author"),
+            Some(&Value::String("Sam".to_string()))
+        );
+    }
+
+    #[test]
+    fn formats_scalar_title_and_date() {
+        let (front_matter, _) = FrontMatter::extract(
+            "---
+title: 1984
+date: 2024
+---",
+        )
+        .unwrap();
+
+        assert_eq!(front_matter.title().as_deref(), Some("1984"));
+        assert_eq!(front_matter.date().as_deref(), Some("2024"));
+
+        let (front_matter, _) = FrontMatter::extract("+++\ntitle = true\ndate = []\n+++").unwrap();
+
+        assert_eq!(front_matter.title().as_deref(), Some("true"));
+        assert_eq!(front_matter.date(), None);
+    }
+
+    #[test]
+    fn ignores_missing_front_matter() {
+        assert_eq!(FrontMatter::extract("# Title\n---\n"), None);
+        assert_eq!(FrontMatter::extract("---\ntitle: unclosed\n"), None);
+    }
+}
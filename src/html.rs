@@ -0,0 +1,262 @@
+const RAW_TAGS: [&str; 4] = ["script", "pre", "style", "textarea"];
+
+const BLOCK_TAGS: [&str; 62] = [
+    "address",
+    "article",
+    "aside",
+    "base",
+    "basefont",
+    "blockquote",
+    "body",
+    "caption",
+    "center",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "iframe",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "menuitem",
+    "nav",
+    "noframes",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "param",
+    "search",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "track",
+    "ul",
+];
+
+#[derive(Debug, PartialEq)]
+pub enum BlockEnd {
+    Contains(&'static [&'static str]),
+    BlankLine,
+}
+
+impl BlockEnd {
+    pub fn is_reached(&self, line: &str) -> bool {
+        match self {
+            BlockEnd::Contains(terminators) => {
+                let line = line.to_lowercase();
+                terminators
+                    .iter()
+                    .any(|terminator| line.contains(terminator))
+            }
+            BlockEnd::BlankLine => line.trim().is_empty(),
+        }
+    }
+}
+
+pub fn block_start(line: &str, interrupts_paragraph: bool) -> Option<BlockEnd> {
+    let line = line.trim_start_matches(' ');
+    let lower = line.to_lowercase();
+
+    if !lower.starts_with('<') {
+        return None;
+    }
+
+    if RAW_TAGS.iter().any(|tag| {
+        lower[1..]
+            .strip_prefix(tag)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['>', ' ', '\t']))
+    }) {
+        return Some(BlockEnd::Contains(&[
+            "</script>",
+            "</pre>",
+            "</style>",
+            "</textarea>",
+        ]));
+    }
+    if lower.starts_with("<!--") {
+        return Some(BlockEnd::Contains(&["-->"]));
+    }
+    if lower.starts_with("<?") {
+        return Some(BlockEnd::Contains(&["?>"]));
+    }
+    if lower.starts_with("<![cdata[") {
+        return Some(BlockEnd::Contains(&["]]>"]));
+    }
+    if lower[1..]
+        .strip_prefix('!')
+        .is_some_and(|rest| rest.starts_with(|char: char| char.is_ascii_alphabetic()))
+    {
+        return Some(BlockEnd::Contains(&[">"]));
+    }
+
+    let name = lower[1..].strip_prefix('/').unwrap_or(&lower[1..]);
+    if BLOCK_TAGS.iter().any(|tag| {
+        name.strip_prefix(tag).is_some_and(|rest| {
+            rest.is_empty() || rest.starts_with(['>', ' ', '\t']) || rest.starts_with("/>")
+        })
+    }) {
+        return Some(BlockEnd::BlankLine);
+    }
+
+    match tag_length(line) {
+        Some(length) if !interrupts_paragraph && line[length..].trim().is_empty() => {
+            Some(BlockEnd::BlankLine)
+        }
+        _ => None,
+    }
+}
+
+pub fn tag_length(text: &str) -> Option<usize> {
+    let rest = text.strip_prefix('<')?;
+
+    if let Some(comment) = rest.strip_prefix("!--") {
+        return comment.find("-->").map(|index| 4 + index + 3);
+    }
+    if let Some(instruction) = rest.strip_prefix('?') {
+        return instruction.find("?>").map(|index| 2 + index + 2);
+    }
+    if let Some(data) = rest.strip_prefix("![CDATA[") {
+        return data.find("]]>").map(|index| 9 + index + 3);
+    }
+    if let Some(declaration) = rest.strip_prefix('!') {
+        if declaration.starts_with(|char: char| char.is_ascii_alphabetic()) {
+            return declaration.find('>').map(|index| 2 + index + 1);
+        }
+        return None;
+    }
+    if let Some(closing) = rest.strip_prefix('/') {
+        let name = name_length(closing)?;
+        let after = closing[name..].trim_start();
+        return after.starts_with('>').then(|| text.len() - after.len() + 1);
+    }
+
+    let mut position = 1 + name_length(rest)?;
+    loop {
+        let after = &text[position..];
+        let trimmed = after.trim_start();
+        let whitespace = after.len() - trimmed.len();
+
+        if trimmed.starts_with('>') {
+            return Some(position + whitespace + 1);
+        }
+        if trimmed.starts_with("/>") {
+            return Some(position + whitespace + 2);
+        }
+        if whitespace == 0 {
+            return None;
+        }
+
+        position += whitespace + attribute_length(trimmed)?;
+    }
+}
+
+fn name_length(text: &str) -> Option<usize> {
+    if !text.starts_with(|char: char| char.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    Some(
+        text.find(|char: char| !char.is_ascii_alphanumeric() && char != '-')
+            .unwrap_or(text.len()),
+    )
+}
+
+fn attribute_length(text: &str) -> Option<usize> {
+    if !text.starts_with(|char: char| char.is_ascii_alphabetic() || char == '_' || char == ':') {
+        return None;
+    }
+
+    let name = text
+        .find(|char: char| !char.is_ascii_alphanumeric() && !"_.:-".contains(char))
+        .unwrap_or(text.len());
+    let after = &text[name..];
+    let trimmed = after.trim_start();
+
+    let Some(value) = trimmed.strip_prefix('=') else {
+        return Some(name);
+    };
+    let value = value.trim_start();
+    let offset = text.len() - value.len();
+
+    let length = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].find(quote)? + 2,
+        _ => value
+            .find(|char: char| char.is_whitespace() || "\"'=<>`".contains(char))
+            .unwrap_or(value.len()),
+    };
+
+    if length == 0 {
+        None
+    } else {
+        Some(offset + length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_tags() {
+        assert_eq!(tag_length("<a href=\"x\">link"), Some(12));
+        assert_eq!(tag_length("<br/>"), Some(5));
+        assert_eq!(tag_length("<img src=a.png alt='b c' />x"), Some(27));
+        assert_eq!(tag_length("</span  >"), Some(9));
+        assert_eq!(tag_length("<!-- note -->rest"), Some(13));
+        assert_eq!(tag_length("<3 you"), None);
+        assert_eq!(tag_length("<a href=\"x>"), None);
+        assert_eq!(tag_length("<a/b>"), None);
+    }
+
+    #[test]
+    fn detects_block_starts() {
+        assert_eq!(
+            block_start("<div class=\"note\">", true),
+            Some(BlockEnd::BlankLine)
+        );
+        assert_eq!(block_start("</DIV>", true), Some(BlockEnd::BlankLine));
+        assert_eq!(
+            block_start("<!-- note", true),
+            Some(BlockEnd::Contains(&["-->"]))
+        );
+        assert_eq!(
+            block_start("<custom-tag>", false),
+            Some(BlockEnd::BlankLine)
+        );
+        assert_eq!(block_start("<custom-tag>", true), None);
+        assert_eq!(block_start("<span>text</span>", false), None);
+        assert!(block_start("<pre>", true).is_some_and(|end| end.is_reached("x</PRE>")));
+    }
+}
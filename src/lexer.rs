@@ -1,7 +1,7 @@
 use std::fmt::{self, Display};
 
 pub struct Lexer {
-    tokens: Vec<Token>,
+    tokens: Vec<(Token, Span)>,
 }
 
 impl Default for Lexer {
@@ -15,39 +15,68 @@ impl Lexer {
         Lexer { tokens: Vec::new() }
     }
 
-    pub fn tokenize(mut self, contents: String) -> Vec<Token> {
-        for char in contents.chars() {
-            let token = Token::new(char);
-            self.tokens.push(token);
+    pub fn tokenize(self, contents: String) -> Vec<Token> {
+        self.tokenize_with_spans(contents)
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect()
+    }
+
+    pub fn tokenize_with_spans(mut self, contents: String) -> Vec<(Token, Span)> {
+        let mut offset = if contents.starts_with('\u{feff}') {
+            '\u{feff}'.len_utf8()
+        } else {
+            0
+        };
+
+        for line in split_lines(&contents[offset..]) {
+            let content = line.trim_end_matches(['\r', '\n']);
+            for (index, char) in content.char_indices() {
+                let start = offset + index;
+                let end = start + char.len_utf8();
+                self.tokens.push((Token::new(char), Span { start, end }));
+            }
+
+            if content.len() < line.len() {
+                let start = offset + content.len();
+                let end = offset + line.len();
+                self.tokens.push((Token::NewLine, Span { start, end }));
+            }
+            offset += line.len();
         }
 
         self.combine_tokens()
     }
 
-    pub fn combine_tokens(self) -> Vec<Token> {
-        let mut combined_tokens: Vec<Token> = Vec::new();
+    pub fn combine_tokens(self) -> Vec<(Token, Span)> {
+        let mut combined_tokens: Vec<(Token, Span)> = Vec::new();
         let iterator = self.tokens.into_iter();
 
-        for token in iterator {
+        for (token, span) in iterator {
             let last = combined_tokens.last_mut();
 
             match (last, token) {
-                (Some(Token::Word(last_word)), Token::Word(word)) => {
+                (Some((Token::Word(last_word), last_span)), Token::Word(word)) => {
                     *last_word += &word;
+                    last_span.end = span.end;
                 }
-                (Some(Token::Asterisk(last_count)), Token::Asterisk(count)) => {
+                (Some((Token::Asterisk(last_count), last_span)), Token::Asterisk(count)) => {
                     *last_count += count;
+                    last_span.end = span.end;
                 }
-                (Some(Token::Heading(last_count)), Token::Heading(count)) => {
+                (Some((Token::Heading(last_count), last_span)), Token::Heading(count)) => {
                     *last_count += count;
+                    last_span.end = span.end;
                 }
-                (Some(Token::WhiteSpace(last_count)), Token::WhiteSpace(count)) => {
+                (Some((Token::WhiteSpace(last_count), last_span)), Token::WhiteSpace(count)) => {
                     *last_count += count;
+                    last_span.end = span.end;
                 }
-                (Some(Token::Number(last_number)), Token::Number(number)) => {
+                (Some((Token::Number(last_number), last_span)), Token::Number(number)) => {
                     *last_number = *last_number * 10 + number;
+                    last_span.end = span.end;
                 }
-                (_, token) => combined_tokens.push(token),
+                (_, token) => combined_tokens.push((token, span)),
             }
         }
 
@@ -55,11 +84,40 @@ impl Lexer {
     }
 }
 
-#[derive(Debug, PartialEq)]
+pub fn split_lines(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+
+        let end = rest.find(['\n', '\r']).map_or(rest.len(), |index| {
+            if rest[index..].starts_with("\r\n") {
+                index + 2
+            } else {
+                index + 1
+            }
+        });
+        let (line, remaining) = rest.split_at(end);
+        rest = remaining;
+
+        Some(line)
+    })
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     Word(String),
     Heading(usize),
     WhiteSpace(usize),
+    Tab,
     Asterisk(usize),
     Number(usize),
     Dot,
@@ -69,6 +127,8 @@ pub enum Token {
     RParen,
     LBracket,
     RBracket,
+    LAngle,
+    Pipe,
 }
 
 impl Display for Token {
@@ -77,6 +137,7 @@ impl Display for Token {
             Token::Word(word) => word.to_string(),
             Token::Heading(count) => "#".repeat(*count),
             Token::WhiteSpace(count) => " ".repeat(*count),
+            Token::Tab => "\t".to_string(),
             Token::Asterisk(count) => "*".repeat(*count),
             Token::Number(number) => number.to_string(),
             Token::Dot => ".".to_string(),
@@ -86,6 +147,8 @@ impl Display for Token {
             Token::RParen => ")".to_string(),
             Token::LBracket => "[".to_string(),
             Token::RBracket => "]".to_string(),
+            Token::LAngle => "<".to_string(),
+            Token::Pipe => "|".to_string(),
         };
 
         write!(f, "{}", output)
@@ -98,6 +161,7 @@ impl Token {
             '\n' => Token::NewLine,
             '#' => Token::Heading(1),
             ' ' => Token::WhiteSpace(1),
+            '\t' => Token::Tab,
             '*' => Token::Asterisk(1),
             '-' => Token::Hyphen,
             '.' => Token::Dot,
@@ -105,6 +169,8 @@ impl Token {
             ')' => Token::RParen,
             '[' => Token::LBracket,
             ']' => Token::RBracket,
+            '<' => Token::LAngle,
+            '|' => Token::Pipe,
             '0'..='9' => Token::Number(char.to_digit(10).unwrap() as usize),
             _ => Token::Word(char.to_string()),
         }
@@ -120,6 +186,7 @@ impl Token {
             (Token::Word(_), Token::Word(_))
                 | (Token::Heading(_), Token::Heading(_))
                 | (Token::WhiteSpace(_), Token::WhiteSpace(_))
+                | (Token::Tab, Token::Tab)
                 | (Token::Asterisk(_), Token::Asterisk(_))
                 | (Token::Number(_), Token::Number(_))
                 | (Token::Dot, Token::Dot)
@@ -129,6 +196,8 @@ impl Token {
                 | (Token::RParen, Token::RParen)
                 | (Token::LBracket, Token::LBracket)
                 | (Token::RBracket, Token::RBracket)
+                | (Token::LAngle, Token::LAngle)
+                | (Token::Pipe, Token::Pipe)
         )
     }
 }
@@ -165,6 +234,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lexes_tabs() {
+        let lexer = Lexer::new();
+        let tokens = lexer.tokenize(String::from("\t\t-\t x"));
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Tab,
+                Token::Tab,
+                Token::Hyphen,
+                Token::Tab,
+                Token::WhiteSpace(1),
+                Token::Word("x".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn normalizes_line_endings() {
+        let lexer = Lexer::new();
+        let tokens = lexer.tokenize(String::from("\u{feff}# Hi\r\n- [x]\rok\r\n"));
+
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Heading(1),
+                Token::WhiteSpace(1),
+                Token::Word("Hi".to_string()),
+                Token::NewLine,
+                Token::Hyphen,
+                Token::WhiteSpace(1),
+                Token::LBracket,
+                Token::Word("x".to_string()),
+                Token::RBracket,
+                Token::NewLine,
+                Token::Word("ok".to_string()),
+                Token::NewLine,
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_original_spans() {
+        let lexer = Lexer::new();
+        let tokens = lexer.tokenize_with_spans(String::from("\u{feff}ab\r\ncd\re"));
+
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::Word("ab".to_string()), Span { start: 3, end: 5 }),
+                (Token::NewLine, Span { start: 5, end: 7 }),
+                (Token::Word("cd".to_string()), Span { start: 7, end: 9 }),
+                (Token::NewLine, Span { start: 9, end: 10 }),
+                (Token::Word("e".to_string()), Span { start: 10, end: 11 }),
+            ]
+        );
+    }
+
     #[test]
     fn lexes_list() {
         let lexer = Lexer::new();
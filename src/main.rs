@@ -1,20 +1,54 @@
+pub mod attributes;
 pub mod evaluator;
+pub mod front_matter;
+pub mod html;
 pub mod lexer;
+pub mod options;
 pub mod parser;
+pub mod renderer;
+pub mod section;
+pub mod slug;
+pub mod task;
+pub mod template;
+pub mod toc;
 
 use evaluator::Evaluator;
-use std::io::stdin;
-use std::{fs, process};
+use options::Options;
+use std::fs::File;
+use std::io::{stdin, BufWriter, Write};
+use std::{env, fs, process};
+use template::Template;
 
 fn main() {
+    let mut options = Options::default();
+    let mut template = None;
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--standalone" => {
+                template.get_or_insert_with(Template::default);
+            }
+            "--template" => {
+                let filepath = args.next().unwrap_or_default();
+                template = Some(get_template(&filepath));
+            }
+            "--stylesheet" => options.stylesheets.extend(args.next()),
+            "--no-inline-theme" => options.inline_theme = false,
+            arg => {
+                println!("Unknown argument: {}", arg);
+                process::exit(1);
+            }
+        }
+    }
+
     let filepath = read_filepath("Enter the filepath of your markdown file: ", "input.md");
     let contents = get_contents(&filepath);
 
-    let evaluator = Evaluator::new(contents);
-    let output = evaluator.evaluate();
+    let evaluator = Evaluator::with_options(contents, options);
 
     let filepath = read_filepath("Enter the filepath of the output file: ", "index.html");
-    write_to_file(&filepath, output);
+    write_to_file(&filepath, evaluator, template);
 
     println!("Successfully wrote to index.html");
 }
@@ -34,8 +68,17 @@ fn read_filepath(instruction: &str, default: &str) -> String {
     }
 }
 
-fn write_to_file(filepath: &str, contents: String) {
-    match fs::write(filepath, contents) {
+fn write_to_file(filepath: &str, evaluator: Evaluator, template: Option<Template>) {
+    let result = File::create(filepath).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        match template {
+            Some(template) => evaluator.render_standalone_to(&template, &mut writer)?,
+            None => evaluator.render_to(&mut writer)?,
+        }
+        writer.flush()
+    });
+
+    match result {
         Ok(()) => {}
         Err(message) => {
             println!("Err, message: {}", message);
@@ -52,3 +95,13 @@ fn get_contents(filepath: &str) -> String {
         }
     }
 }
+
+fn get_template(filepath: &str) -> Template {
+    match Template::from_file(filepath) {
+        Ok(template) => template,
+        Err(message) => {
+            println!("Could not read template {}: {}", filepath, message);
+            process::exit(1);
+        }
+    }
+}
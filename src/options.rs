@@ -0,0 +1,73 @@
+use crate::renderer::{Classes, HtmlRenderer, Renderer};
+use std::collections::HashMap;
+
+pub type ContainerRenderer = Box<dyn Fn(&str, Option<&str>, &str) -> String>;
+
+pub enum RawHtml {
+    Allow,
+    Escape,
+    Strip,
+}
+
+pub struct Options {
+    pub alerts: HashMap<String, String>,
+    pub containers: HashMap<String, ContainerRenderer>,
+    pub raw_html: RawHtml,
+    pub heading_ids: bool,
+    pub heading_permalinks: bool,
+    pub heading_offset: usize,
+    pub extract_title: bool,
+    pub heading_numbers: bool,
+    pub heading_numbers_start: usize,
+    pub heading_numbers_separator: String,
+    pub sections: bool,
+    pub toc_min_depth: usize,
+    pub toc_max_depth: usize,
+    pub stylesheets: Vec<String>,
+    pub inline_theme: bool,
+    pub classes: Classes,
+    pub interactive_task_lists: bool,
+    pub renderer: Box<dyn Renderer>,
+}
+
+impl Options {
+    pub fn heading_numbers_start(&self) -> usize {
+        self.heading_numbers_start.clamp(1, 6)
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        let alerts = [
+            ("note", "Note"),
+            ("tip", "Tip"),
+            ("important", "Important"),
+            ("warning", "Warning"),
+            ("caution", "Caution"),
+        ]
+        .into_iter()
+        .map(|(kind, title)| (kind.to_string(), title.to_string()))
+        .collect();
+
+        Options {
+            alerts,
+            containers: HashMap::new(),
+            raw_html: RawHtml::Allow,
+            heading_ids: true,
+            heading_permalinks: false,
+            heading_offset: 0,
+            extract_title: false,
+            heading_numbers: false,
+            heading_numbers_start: 1,
+            heading_numbers_separator: ".".to_string(),
+            sections: false,
+            toc_min_depth: 1,
+            toc_max_depth: 6,
+            stylesheets: Vec::new(),
+            inline_theme: true,
+            classes: Classes::default(),
+            interactive_task_lists: false,
+            renderer: Box::new(HtmlRenderer),
+        }
+    }
+}
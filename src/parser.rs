@@ -1,47 +1,747 @@
+use crate::attributes::Attributes;
+use crate::html::{self, BlockEnd};
 use crate::lexer::{Lexer, Token};
+use std::iter::Peekable;
+use std::vec::IntoIter;
 
 pub struct Parser {
     tokens: Vec<Token>,
+    first_line: usize,
 }
 
 impl Parser {
     pub fn new(input: String) -> Parser {
+        Self::with_first_line(input, 1)
+    }
+
+    pub fn with_first_line(input: String, first_line: usize) -> Parser {
         let lexer = Lexer::new();
         let tokens = lexer.tokenize(input);
-        Parser { tokens }
+        Parser { tokens, first_line }
     }
 
     pub fn parse(self) -> Vec<Statement> {
         let lines = Self::prepare_lines(&mut self.tokens.into_iter());
+        Self::parse_lines(lines, self.first_line)
+    }
+
+    fn parse_lines(lines: Vec<Vec<Token>>, first_line: usize) -> Vec<Statement> {
         let mut statements = Vec::new();
+        let total = lines.len();
+        let mut iterator = lines.into_iter().peekable();
+
+        while let Some(line) = iterator.next() {
+            let number = first_line + total - iterator.len() - 1;
+
+            if Self::indentation(&line) >= 4 && !Self::is_blank(&line) {
+                if Self::is_paragraph(statements.last()) {
+                    statements.push(Statement::Plain(Self::parse_trimmed(line)));
+                } else {
+                    statements.append(&mut Self::parse_code_block(line, &mut iterator));
+                }
+                continue;
+            }
+
+            let text = Self::line_to_string(&line);
+            if let Some(end) = html::block_start(&text, Self::is_paragraph(statements.last())) {
+                statements.push(Self::parse_html_block(text, end, &mut iterator));
+                continue;
+            }
+
+            if matches!(text.trim(), "[TOC]" | "[[_TOC_]]") {
+                statements.push(Statement::TableOfContents);
+                continue;
+            }
+
+            if let Some((label, content)) = Self::split_footnote_definition(&line) {
+                statements.append(&mut Self::parse_footnote_definition(
+                    label,
+                    content,
+                    &mut iterator,
+                    number,
+                ));
+                continue;
+            }
+
+            if let Some((colons, kind, title, attributes)) = Self::split_container_opening(&line) {
+                statements.push(Self::parse_container(
+                    colons,
+                    kind,
+                    title,
+                    attributes,
+                    &mut iterator,
+                    number,
+                ));
+                continue;
+            }
+
+            if let Some(content) = Self::split_blockquote_marker(&line) {
+                statements.push(Self::parse_blockquote(content, &mut iterator, number));
+                continue;
+            }
+
+            if let Some(content) = Self::split_definition_marker(&line) {
+                let terms = Self::take_paragraphs(&mut statements);
+                if !terms.is_empty() {
+                    let (definitions, blank_lines) =
+                        Self::parse_definitions(content, &mut iterator, number);
+                    Self::push_definition(&mut statements, terms, definitions);
+                    statements.extend(blank_lines.into_iter().map(Self::parse_line));
+                    continue;
+                }
+            }
+
+            if let Some(alignments) = iterator
+                .peek()
+                .and_then(|next| Self::parse_table_alignments(next))
+            {
+                if line.contains(&Token::Pipe)
+                    && Self::split_table_row(line.clone()).len() == alignments.len()
+                {
+                    iterator.next();
+                    statements.push(Self::parse_table(line, alignments, &mut iterator));
+                    continue;
+                }
+            }
+
+            if let Some(level) = iterator.peek().and_then(|next| Self::setext_level(next)) {
+                if Self::is_paragraph(Some(&Self::parse_line(line.clone()))) {
+                    iterator.next();
+                    let (tokens, attributes) = Self::split_attributes(line);
+                    let mut lines = Self::take_paragraphs(&mut statements);
+                    lines.push(Self::parse_trimmed(tokens));
+                    statements.push(Statement::Heading(
+                        level,
+                        Self::join_lines(lines),
+                        attributes,
+                    ));
+                    continue;
+                }
+            }
+
+            let indentation = Self::indentation(&line);
+            let mut statement = Self::locate(Self::parse_line(line), number);
+            if let Statement::OrderedListItem(.., children)
+            | Statement::UnorderedListItem(.., children) = &mut statement
+            {
+                *children = Self::parse_sub_list(indentation, &mut iterator, number + 1);
+            }
+            statements.push(statement);
+        }
+
+        statements
+    }
+
+    fn locate(mut statement: Statement, line: usize) -> Statement {
+        if let Statement::OrderedListItem(_, Some(task), ..)
+        | Statement::UnorderedListItem(_, Some(task), ..) = &mut statement
+        {
+            task.line = line;
+        }
+
+        statement
+    }
+
+    fn parse_sub_list(
+        indentation: usize,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+        first_line: usize,
+    ) -> Vec<Statement> {
+        let nested = match iterator.peek() {
+            Some(next) if Self::indentation(next) > indentation && !Self::is_blank(next) => {
+                let nested = Self::indentation(next);
+                let first = Self::parse_line(Self::strip_indentation(next.clone(), nested));
+                if !matches!(
+                    first,
+                    Statement::OrderedListItem(..) | Statement::UnorderedListItem(..)
+                ) {
+                    return Vec::new();
+                }
+                nested
+            }
+            _ => return Vec::new(),
+        };
+
+        let mut lines = Vec::new();
+        while let Some(line) =
+            iterator.next_if(|line| Self::indentation(line) > indentation && !Self::is_blank(line))
+        {
+            lines.push(Self::strip_indentation(line, nested));
+        }
+
+        Self::parse_lines(lines, first_line)
+    }
+
+    fn parse_code_block(
+        first: Vec<Token>,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+    ) -> Vec<Statement> {
+        let mut lines = vec![Self::strip_indentation(first, 4)];
+        let blank_lines = Self::collect_indented_lines(&mut lines, iterator);
 
+        let mut code = String::new();
         for line in lines {
-            statements.push(Self::parse_line(line));
+            code += &Self::line_to_string(&line);
+            code.push('\n');
+        }
+
+        let mut statements = vec![Statement::CodeBlock(code)];
+        statements.extend(blank_lines.into_iter().map(Self::parse_line));
+
+        statements
+    }
+
+    fn parse_html_block(
+        first: String,
+        end: BlockEnd,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+    ) -> Statement {
+        let mut reached = end.is_reached(&first);
+        let mut lines = vec![first];
+
+        while !reached {
+            let next = iterator.next_if(|line| end != BlockEnd::BlankLine || !Self::is_blank(line));
+            let Some(line) = next else {
+                break;
+            };
+
+            let text = Self::line_to_string(&line);
+            reached = end.is_reached(&text);
+            lines.push(text);
         }
 
+        Statement::Html(lines.join("\n"))
+    }
+
+    fn line_to_string(tokens: &[Token]) -> String {
+        tokens.iter().map(|token| token.to_string()).collect()
+    }
+
+    fn parse_footnote_definition(
+        label: String,
+        content: Vec<Token>,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+        first_line: usize,
+    ) -> Vec<Statement> {
+        let mut lines = vec![content];
+        let blank_lines = Self::collect_indented_lines(&mut lines, iterator);
+
+        let mut statements = vec![Statement::FootnoteDefinition(
+            label,
+            Self::parse_lines(lines, first_line),
+        )];
+        statements.extend(blank_lines.into_iter().map(Self::parse_line));
+
         statements
     }
 
+    fn block_prefix(tokens: &[Token]) -> Option<Vec<Token>> {
+        if Self::indentation(tokens) >= 4 {
+            return None;
+        }
+
+        Some(Self::strip_indentation(tokens.to_vec(), 3))
+    }
+
+    fn split_footnote_definition(tokens: &[Token]) -> Option<(String, Vec<Token>)> {
+        let tokens = Self::block_prefix(tokens)?;
+        let [Token::LBracket, rest @ ..] = tokens.as_slice() else {
+            return None;
+        };
+        let end = rest.iter().position(|token| *token == Token::RBracket)?;
+        let label = Self::footnote_label(&rest[..end])?;
+
+        let mut content = rest[end + 1..].to_vec();
+        match content.first_mut() {
+            Some(Token::Word(word)) if word.starts_with(':') => {
+                word.remove(0);
+                if word.is_empty() {
+                    content.remove(0);
+                }
+            }
+            _ => return None,
+        }
+
+        Some((label, Self::strip_indentation(content, usize::MAX)))
+    }
+
+    fn footnote_label(tokens: &[Token]) -> Option<String> {
+        let text: String = tokens.iter().map(|token| token.to_string()).collect();
+        let label = text.strip_prefix('^')?;
+
+        if label.is_empty() || label.contains(char::is_whitespace) {
+            None
+        } else {
+            Some(label.to_string())
+        }
+    }
+
+    fn parse_container(
+        colons: usize,
+        kind: String,
+        title: Option<Expression>,
+        attributes: Attributes,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+        opening_line: usize,
+    ) -> Statement {
+        let mut fences = vec![colons];
+        let mut lines = Vec::new();
+
+        for line in iterator.by_ref() {
+            if let Some((colons, info)) = Self::split_container_fence(&line) {
+                if !Self::is_blank(&info) {
+                    fences.push(colons);
+                } else if fences.last().is_some_and(|last| colons >= *last) {
+                    fences.pop();
+                    if fences.is_empty() {
+                        break;
+                    }
+                }
+            }
+
+            lines.push(line);
+        }
+
+        Statement::Container(
+            kind,
+            title,
+            attributes,
+            Self::parse_lines(lines, opening_line + 1),
+        )
+    }
+
+    fn split_container_opening(
+        tokens: &[Token],
+    ) -> Option<(usize, String, Option<Expression>, Attributes)> {
+        let (colons, info) = Self::split_container_fence(tokens)?;
+        let mut iterator = info.into_iter().peekable();
+
+        let mut kind = String::new();
+        while let Some(token) =
+            iterator.next_if(|token| !matches!(token, Token::WhiteSpace(_) | Token::Tab))
+        {
+            kind += &token.to_string();
+        }
+        if kind.is_empty() {
+            return None;
+        }
+
+        let (tokens, attributes) = Self::split_attributes(iterator.collect());
+        let title = Self::parse_trimmed(tokens);
+        if title == Expression::Text(String::new()) {
+            Some((colons, kind, None, attributes))
+        } else {
+            Some((colons, kind, Some(title), attributes))
+        }
+    }
+
+    fn split_container_fence(tokens: &[Token]) -> Option<(usize, Vec<Token>)> {
+        let mut tokens = Self::block_prefix(tokens)?;
+        let colons = match tokens.first_mut() {
+            Some(Token::Word(word)) if word.starts_with(":::") => {
+                let colons = word.chars().take_while(|char| *char == ':').count();
+                word.drain(..colons);
+                if word.is_empty() {
+                    tokens.remove(0);
+                }
+                colons
+            }
+            _ => return None,
+        };
+
+        let mut info = Self::strip_indentation(tokens, usize::MAX);
+        Self::trim_trailing_whitespace(&mut info);
+
+        Some((colons, info))
+    }
+
+    fn parse_blockquote(
+        content: Vec<Token>,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+        first_line: usize,
+    ) -> Statement {
+        let mut lines = vec![content];
+
+        loop {
+            let lazy = lines.last().is_some_and(|line| !Self::is_blank(line));
+
+            if let Some(content) = iterator
+                .peek()
+                .and_then(|next| Self::split_blockquote_marker(next))
+            {
+                iterator.next();
+                lines.push(content);
+            } else if let Some(line) = iterator.next_if(|next| {
+                lazy && !Self::is_blank(next)
+                    && matches!(Self::parse_line(next.clone()), Statement::Plain(_))
+            }) {
+                lines.push(line);
+            } else {
+                break;
+            }
+        }
+
+        match lines.first().and_then(|first| Self::alert_kind(first)) {
+            Some(kind) => {
+                Statement::Alert(kind, Self::parse_lines(lines.split_off(1), first_line + 1))
+            }
+            None => Statement::BlockQuote(Self::parse_lines(lines, first_line)),
+        }
+    }
+
+    fn split_blockquote_marker(tokens: &[Token]) -> Option<Vec<Token>> {
+        let mut tokens = Self::block_prefix(tokens)?;
+        match tokens.first_mut() {
+            Some(Token::Word(word)) if word.starts_with('>') => {
+                word.remove(0);
+                if word.is_empty() {
+                    tokens.remove(0);
+                }
+            }
+            _ => return None,
+        }
+
+        Some(Self::strip_indentation(tokens, 1))
+    }
+
+    fn alert_kind(tokens: &[Token]) -> Option<String> {
+        let mut tokens = Self::strip_indentation(tokens.to_vec(), usize::MAX);
+        Self::trim_trailing_whitespace(&mut tokens);
+
+        match tokens.as_slice() {
+            [Token::LBracket, Token::Word(word), Token::RBracket] => {
+                let kind = word.strip_prefix('!')?;
+                if kind
+                    .chars()
+                    .all(|char| char.is_alphanumeric() || char == '_')
+                {
+                    Some(kind.to_string())
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_definitions(
+        mut content: Vec<Token>,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+        mut first_line: usize,
+    ) -> (Vec<Vec<Statement>>, Vec<Vec<Token>>) {
+        let mut definitions = Vec::new();
+
+        loop {
+            let mut lines = vec![content];
+            let blank_lines = Self::collect_indented_lines(&mut lines, iterator);
+            let line_count = lines.len() + blank_lines.len();
+            definitions.push(Self::parse_lines(lines, first_line));
+            first_line += line_count;
+
+            match iterator
+                .peek()
+                .and_then(|next| Self::split_definition_marker(next))
+            {
+                Some(next) => {
+                    iterator.next();
+                    content = next;
+                }
+                None => return (definitions, blank_lines),
+            }
+        }
+    }
+
+    fn split_definition_marker(tokens: &[Token]) -> Option<Vec<Token>> {
+        let mut tokens = Self::block_prefix(tokens)?;
+        match tokens.as_slice() {
+            [Token::Word(word)] | [Token::Word(word), Token::WhiteSpace(_) | Token::Tab, ..]
+                if word == ":" =>
+            {
+                tokens.remove(0);
+                Some(Self::strip_indentation(tokens, usize::MAX))
+            }
+            _ => None,
+        }
+    }
+
+    fn take_paragraphs(statements: &mut Vec<Statement>) -> Vec<Expression> {
+        let mut paragraphs = Vec::new();
+
+        while Self::is_paragraph(statements.last()) {
+            if let Some(Statement::Plain(expression)) = statements.pop() {
+                paragraphs.push(expression);
+            }
+        }
+        paragraphs.reverse();
+
+        paragraphs
+    }
+
+    fn join_lines(mut lines: Vec<Expression>) -> Expression {
+        if lines.len() == 1 {
+            return lines.remove(0);
+        }
+
+        let mut joined = Vec::new();
+        for line in lines {
+            if !joined.is_empty() {
+                joined.push(Expression::Text("\n".to_string()));
+            }
+            joined.push(line);
+        }
+
+        Expression::Vec(joined)
+    }
+
+    fn push_definition(
+        statements: &mut Vec<Statement>,
+        terms: Vec<Expression>,
+        definitions: Vec<Vec<Statement>>,
+    ) {
+        let blank = Statement::Plain(Expression::Text(String::new()));
+        let previous = statements.iter().rposition(|statement| *statement != blank);
+
+        if let Some(index) = previous {
+            if let Statement::DefinitionList(_) = statements[index] {
+                statements.truncate(index + 1);
+            }
+        }
+
+        match statements.last_mut() {
+            Some(Statement::DefinitionList(items)) => items.push((terms, definitions)),
+            _ => statements.push(Statement::DefinitionList(vec![(terms, definitions)])),
+        }
+    }
+
+    fn collect_indented_lines(
+        lines: &mut Vec<Vec<Token>>,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+    ) -> Vec<Vec<Token>> {
+        let mut blank_lines = Vec::new();
+
+        while let Some(line) =
+            iterator.next_if(|line| Self::is_blank(line) || Self::indentation(line) >= 4)
+        {
+            if Self::is_blank(&line) {
+                blank_lines.push(line);
+            } else {
+                for blank_line in blank_lines.drain(..) {
+                    lines.push(Self::strip_indentation(blank_line, 4));
+                }
+                lines.push(Self::strip_indentation(line, 4));
+            }
+        }
+
+        blank_lines
+    }
+
+    fn parse_table(
+        header: Vec<Token>,
+        alignments: Vec<Alignment>,
+        iterator: &mut Peekable<IntoIter<Vec<Token>>>,
+    ) -> Statement {
+        let columns = alignments.len();
+        let header = Self::parse_table_row(header, columns);
+        let mut rows = Vec::new();
+
+        while let Some(line) = iterator.next_if(|line| {
+            !Self::is_blank(line)
+                && Self::indentation(line) < 4
+                && matches!(Self::parse_line(line.clone()), Statement::Plain(_))
+        }) {
+            rows.push(Self::parse_table_row(line, columns));
+        }
+
+        Statement::Table(alignments, header, rows)
+    }
+
+    fn parse_table_row(tokens: Vec<Token>, columns: usize) -> Vec<Expression> {
+        let mut cells: Vec<Expression> = Self::split_table_row(tokens)
+            .into_iter()
+            .map(Self::parse_trimmed)
+            .collect();
+        cells.resize_with(columns, || Expression::Text(String::new()));
+
+        cells
+    }
+
+    fn parse_table_alignments(tokens: &[Token]) -> Option<Vec<Alignment>> {
+        if !tokens.contains(&Token::Pipe) {
+            return None;
+        }
+
+        let mut alignments = Vec::new();
+
+        for cell in Self::split_table_row(tokens.to_vec()) {
+            let text: String = cell.iter().map(|token| token.to_string()).collect();
+            let text = text.trim();
+            let (left, text) = match text.strip_prefix(':') {
+                Some(text) => (true, text),
+                None => (false, text),
+            };
+            let (right, text) = match text.strip_suffix(':') {
+                Some(text) => (true, text),
+                None => (false, text),
+            };
+
+            if text.is_empty() || !text.chars().all(|char| char == '-') {
+                return None;
+            }
+
+            alignments.push(match (left, right) {
+                (true, true) => Alignment::Center,
+                (true, false) => Alignment::Left,
+                (false, true) => Alignment::Right,
+                (false, false) => Alignment::None,
+            });
+        }
+
+        Some(alignments)
+    }
+
+    fn split_table_row(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+        let mut tokens = Self::strip_indentation(tokens, usize::MAX);
+        Self::trim_trailing_whitespace(&mut tokens);
+
+        if let Some(Token::Pipe) = tokens.first() {
+            tokens.remove(0);
+        }
+        if !matches!(tokens.as_slice(), [.., Token::Word(word), Token::Pipe] if word.ends_with('\\'))
+        {
+            if let Some(Token::Pipe) = tokens.last() {
+                tokens.pop();
+            }
+        }
+
+        let mut cells = vec![Vec::new()];
+        for token in tokens {
+            let Some(cell) = cells.last_mut() else {
+                continue;
+            };
+
+            match (cell.last_mut(), token) {
+                (Some(Token::Word(word)), Token::Pipe) if word.ends_with('\\') => {
+                    word.pop();
+                    word.push('|');
+                }
+                (_, Token::Pipe) => cells.push(Vec::new()),
+                (_, token) => cell.push(token),
+            }
+        }
+
+        cells
+    }
+
+    fn is_paragraph(statement: Option<&Statement>) -> bool {
+        match statement {
+            Some(Statement::Plain(expression)) => *expression != Expression::Text(String::new()),
+            _ => false,
+        }
+    }
+
+    fn is_blank(tokens: &[Token]) -> bool {
+        tokens
+            .iter()
+            .all(|token| matches!(token, Token::WhiteSpace(_) | Token::Tab))
+    }
+
+    fn indentation(tokens: &[Token]) -> usize {
+        let mut column = 0;
+
+        for token in tokens {
+            match token {
+                Token::WhiteSpace(count) => column += count,
+                Token::Tab => column = Self::next_tab_stop(column),
+                _ => break,
+            }
+        }
+
+        column
+    }
+
+    fn strip_indentation(tokens: Vec<Token>, columns: usize) -> Vec<Token> {
+        let mut stripped = Vec::new();
+        let mut column = 0;
+        let mut iterator = tokens.into_iter().peekable();
+
+        while column < columns {
+            let next_column = match iterator.peek() {
+                Some(Token::WhiteSpace(count)) => column + count,
+                Some(Token::Tab) => Self::next_tab_stop(column),
+                _ => break,
+            };
+            iterator.next();
+
+            if next_column > columns {
+                stripped.push(Token::WhiteSpace(next_column - columns));
+            }
+            column = next_column;
+        }
+
+        stripped.extend(iterator);
+        stripped
+    }
+
+    fn next_tab_stop(column: usize) -> usize {
+        (column / 4 + 1) * 4
+    }
+
+    fn setext_level(tokens: &[Token]) -> Option<usize> {
+        let mut iterator = tokens.iter().peekable();
+        if let Some(Token::WhiteSpace(count)) = iterator.peek() {
+            if *count > 3 {
+                return None;
+            }
+            iterator.next();
+        }
+
+        let level = match iterator.peek() {
+            Some(Token::Word(word)) if word.chars().all(|char| char == '=') => 1,
+            Some(Token::Hyphen) => 2,
+            _ => return None,
+        };
+
+        let mut trailing = false;
+        for token in iterator {
+            match token {
+                Token::Word(word)
+                    if !trailing && level == 1 && word.chars().all(|char| char == '=') => {}
+                Token::Hyphen if !trailing && level == 2 => {}
+                Token::WhiteSpace(_) | Token::Tab => trailing = true,
+                _ => return None,
+            }
+        }
+
+        Some(level)
+    }
+
     fn parse_line(tokens: Vec<Token>) -> Statement {
-        let mut iterator = tokens.into_iter();
+        let mut iterator = tokens.into_iter().peekable();
+        if let Some(Token::WhiteSpace(count)) = iterator.peek() {
+            if *count <= 3 {
+                iterator.next();
+            }
+        }
         let first = iterator.next();
 
         match first {
-            Some(Token::Heading(count)) => {
+            Some(Token::Heading(count)) if count <= 6 => {
                 let token = iterator.next();
                 let tokens = iterator.collect();
                 match token {
-                    Some(Token::WhiteSpace(_)) => {
-                        Statement::Heading(count, Self::parse_expression(tokens))
+                    Some(Token::WhiteSpace(_) | Token::Tab) => {
+                        let (expression, attributes) = Self::parse_heading_content(tokens);
+                        Statement::Heading(count, expression, attributes)
                     }
                     Some(token) => Statement::Plain(Self::parse_expression(Self::prepend_array(
                         tokens,
                         vec![Token::Heading(count), token],
                     ))),
-                    None => Statement::Plain(Self::parse_expression(Self::prepend_array(
-                        tokens,
-                        vec![Token::Heading(count)],
-                    ))),
+                    None => Statement::Heading(
+                        count,
+                        Expression::Text(String::new()),
+                        Attributes::default(),
+                    ),
                 }
             }
             Some(Token::Number(number)) => {
@@ -66,127 +766,31 @@ impl Parser {
                             }
                         };
 
-                        if !next_token.expect(&Token::WhiteSpace(1)) {
+                        if !matches!(next_token, Token::WhiteSpace(_) | Token::Tab) {
                             return Self::get_plain_statement(
                                 &mut iterator,
                                 vec![Token::Number(number), Token::Dot, next_token],
                             );
                         }
 
+                        let (task, tokens) = Self::split_task(Self::strip_indentation(
+                            iterator.collect(),
+                            usize::MAX,
+                        ));
                         Statement::OrderedListItem(
                             number,
-                            Self::parse_expression(iterator.collect()),
+                            task,
+                            Self::parse_expression(tokens),
+                            Vec::new(),
                         )
                     }
                     None => Self::get_plain_statement(&mut iterator, vec![Token::Number(number)]),
                 }
             }
-            Some(Token::Hyphen) => {
-                let token = iterator.next();
-                let tokens: Vec<Token> = iterator.collect();
-                match token {
-                    Some(Token::WhiteSpace(_)) => {
-                        let mut iterator = tokens.into_iter();
-
-                        match iterator.next() {
-                            Some(Token::LBracket) => {
-                                let next = iterator.next();
-                                let mut checked = false;
-
-                                match next {
-                                    Some(Token::WhiteSpace(1)) => {}
-                                    Some(Token::Word(word)) if word == "x" => {
-                                        checked = true;
-                                    }
-                                    Some(token) => {
-                                        return Statement::Plain(Self::parse_expression(
-                                            Self::prepend_array(
-                                                iterator.collect(),
-                                                vec![Token::LBracket, token],
-                                            ),
-                                        ));
-                                    }
-                                    None => {
-                                        return Statement::Plain(Self::parse_expression(vec![
-                                            Token::LBracket,
-                                        ]))
-                                    }
-                                }
-
-                                let checked_token = if checked {
-                                    Token::Word("x".to_string())
-                                } else {
-                                    Token::WhiteSpace(1)
-                                };
-
-                                match iterator.next() {
-                                    Some(Token::RBracket) => {}
-                                    Some(token) => {
-                                        return Statement::UnorderedListItem(
-                                            Self::parse_expression(Self::prepend_array(
-                                                iterator.collect(),
-                                                vec![Token::LBracket, checked_token, token],
-                                            )),
-                                        )
-                                    }
-                                    None => {
-                                        return Statement::UnorderedListItem(
-                                            Self::parse_expression(Self::prepend_array(
-                                                iterator.collect(),
-                                                vec![Token::LBracket, checked_token],
-                                            )),
-                                        )
-                                    }
-                                }
-
-                                match iterator.next() {
-                                    Some(Token::WhiteSpace(_)) => {}
-                                    Some(token) => {
-                                        return Statement::UnorderedListItem(
-                                            Self::parse_expression(Self::prepend_array(
-                                                iterator.collect(),
-                                                vec![
-                                                    Token::LBracket,
-                                                    checked_token,
-                                                    Token::RBracket,
-                                                    token,
-                                                ],
-                                            )),
-                                        )
-                                    }
-                                    None => {
-                                        return Statement::UnorderedListItem(
-                                            Self::parse_expression(vec![
-                                                Token::LBracket,
-                                                checked_token,
-                                                Token::RBracket,
-                                            ]),
-                                        )
-                                    }
-                                }
-
-                                Statement::TaskListItem(
-                                    checked,
-                                    Self::parse_expression(iterator.collect()),
-                                )
-                            }
-                            Some(token) => Statement::UnorderedListItem(Self::parse_expression(
-                                Self::prepend_array(iterator.collect(), vec![token]),
-                            )),
-                            None => Statement::UnorderedListItem(Self::parse_expression(
-                                iterator.collect(),
-                            )),
-                        }
-                    }
-                    Some(token) => Statement::Plain(Self::parse_expression(Self::prepend_array(
-                        tokens,
-                        vec![Token::Hyphen, token],
-                    ))),
-                    None => Statement::Plain(Self::parse_expression(Self::prepend_array(
-                        tokens,
-                        vec![Token::Hyphen],
-                    ))),
-                }
+            Some(Token::Hyphen) => Self::parse_bullet_item('-', iterator.collect()),
+            Some(Token::Asterisk(1)) => Self::parse_bullet_item('*', iterator.collect()),
+            Some(Token::Word(word)) if word == "+" => {
+                Self::parse_bullet_item('+', iterator.collect())
             }
             Some(token) => Statement::Plain(Self::parse_expression(Self::prepend_array(
                 iterator.collect(),
@@ -196,204 +800,1075 @@ impl Parser {
         }
     }
 
-    fn get_plain_statement(
-        iterator: &mut dyn Iterator<Item = Token>,
-        prepend: Vec<Token>,
-    ) -> Statement {
-        Statement::Plain(Self::parse_expression(Self::prepend_array(
-            iterator.collect(),
-            prepend,
-        )))
+    fn parse_bullet_item(bullet: char, tokens: Vec<Token>) -> Statement {
+        match tokens.first() {
+            Some(Token::WhiteSpace(_) | Token::Tab) => {
+                let (task, tokens) = Self::split_task(Self::strip_indentation(tokens, usize::MAX));
+                Statement::UnorderedListItem(
+                    bullet,
+                    task,
+                    Self::parse_expression(tokens),
+                    Vec::new(),
+                )
+            }
+            _ => Statement::Plain(Self::parse_expression(Self::prepend_array(
+                tokens,
+                vec![Token::new(bullet)],
+            ))),
+        }
+    }
+
+    fn split_task(tokens: Vec<Token>) -> (Option<Task>, Vec<Token>) {
+        let checked = match tokens.as_slice() {
+            [Token::LBracket, Token::WhiteSpace(1), Token::RBracket, Token::WhiteSpace(_) | Token::Tab, ..] => {
+                false
+            }
+            [Token::LBracket, Token::Word(word), Token::RBracket, Token::WhiteSpace(_) | Token::Tab, ..]
+                if word == "x" || word == "X" =>
+            {
+                true
+            }
+            _ => return (None, tokens),
+        };
+
+        let task = Task { checked, line: 0 };
+        (
+            Some(task),
+            Self::strip_indentation(tokens[4..].to_vec(), usize::MAX),
+        )
+    }
+
+    fn parse_heading_content(tokens: Vec<Token>) -> (Expression, Attributes) {
+        let (mut tokens, attributes) = Self::split_attributes(tokens);
+        Self::trim_trailing_whitespace(&mut tokens);
+
+        if matches!(
+            tokens.as_slice(),
+            [Token::Heading(_)] | [.., Token::WhiteSpace(_) | Token::Tab, Token::Heading(_)]
+        ) {
+            tokens.pop();
+        }
+
+        (Self::parse_trimmed(tokens), attributes)
+    }
+
+    fn split_attributes(tokens: Vec<Token>) -> (Vec<Token>, Attributes) {
+        let text = Self::line_to_string(&tokens);
+
+        match Attributes::split_trailing(&text) {
+            Some((rest, attributes)) => (Lexer::new().tokenize(rest.to_string()), attributes),
+            None => (tokens, Attributes::default()),
+        }
+    }
+
+    fn parse_trimmed(tokens: Vec<Token>) -> Expression {
+        let mut tokens = Self::strip_indentation(tokens, usize::MAX);
+        Self::trim_trailing_whitespace(&mut tokens);
+
+        if tokens.is_empty() {
+            Expression::Text(String::new())
+        } else {
+            Self::parse_expression(tokens)
+        }
+    }
+
+    fn trim_trailing_whitespace(tokens: &mut Vec<Token>) {
+        while let Some(Token::WhiteSpace(_) | Token::Tab) = tokens.last() {
+            tokens.pop();
+        }
+    }
+
+    fn get_plain_statement(
+        iterator: &mut dyn Iterator<Item = Token>,
+        prepend: Vec<Token>,
+    ) -> Statement {
+        Statement::Plain(Self::parse_expression(Self::prepend_array(
+            iterator.collect(),
+            prepend,
+        )))
     }
 
-    fn parse_expression(tokens: Vec<Token>) -> Expression {
-        let mut expressions = Vec::new();
-        let mut iterator = tokens.into_iter();
+    fn parse_expression(tokens: Vec<Token>) -> Expression {
+        let mut text = String::new();
+        let mut ends = Vec::with_capacity(tokens.len());
+        for token in &tokens {
+            text += &token.to_string();
+            ends.push(text.len());
+        }
+
+        let mut expressions = Vec::new();
+        let mut iterator = tokens.into_iter();
+
+        while let Some(token) = iterator.next() {
+            match token {
+                Token::Word(word) => {
+                    Self::append_to_last(&mut expressions, word);
+                }
+                Token::Asterisk(left_count) => {
+                    let mut tokens = Vec::new();
+                    let mut right_count = 0;
+
+                    for token in iterator.by_ref() {
+                        match token {
+                            Token::Asterisk(count) => {
+                                right_count = count;
+                                break;
+                            }
+                            token => tokens.push(token),
+                        }
+                    }
+
+                    if left_count > right_count {
+                        expressions.push(Expression::Text("*".repeat(left_count - right_count)));
+                    }
+
+                    let expression = match (left_count, right_count) {
+                        (3..=usize::MAX, 3..=usize::MAX) => {
+                            Expression::BoldItalic(Box::new(Self::parse_expression(tokens)))
+                        }
+                        (2..=usize::MAX, 2..=usize::MAX) => {
+                            Expression::Bold(Box::new(Self::parse_expression(tokens)))
+                        }
+                        (1..=usize::MAX, 1..=usize::MAX) => {
+                            Expression::Italic(Box::new(Self::parse_expression(tokens)))
+                        }
+                        _ => Expression::Text("*".repeat(left_count + right_count)),
+                    };
+                    expressions.push(expression);
+
+                    if right_count > left_count {
+                        expressions.push(Expression::Text("*".repeat(right_count - left_count)));
+                    }
+                }
+                Token::LBracket => {
+                    let mut title_tokens = Vec::new();
+                    let mut link_tokens = Vec::new();
+
+                    let mut found = false;
+                    for token in iterator.by_ref() {
+                        match token {
+                            Token::RBracket => {
+                                found = true;
+                                break;
+                            }
+                            token => title_tokens.push(token),
+                        }
+                    }
+
+                    if !found {
+                        expressions.push(Self::parse_expression(Self::prepend_array(
+                            title_tokens,
+                            vec![Token::LBracket.to_word()],
+                        )));
+                        continue;
+                    }
+
+                    if let Some(label) = Self::footnote_label(&title_tokens) {
+                        expressions.push(Expression::FootnoteReference(label));
+                        continue;
+                    }
+
+                    let next = iterator.next();
+                    let mut found = false;
+                    if let Some(Token::LParen) = &next {
+                        for token in iterator.by_ref() {
+                            match token {
+                                Token::RParen => {
+                                    found = true;
+                                    break;
+                                }
+                                token => link_tokens.push(token),
+                            }
+                        }
+                    }
+
+                    if !found {
+                        let mut tokens =
+                            Self::prepend_array(title_tokens, vec![Token::LBracket.to_word()]);
+                        tokens.push(Token::RBracket.to_word());
+                        if let Some(next) = next {
+                            tokens.push(next);
+                        }
+                        tokens.append(&mut link_tokens);
+                        expressions.push(Self::parse_expression(tokens));
+                        continue;
+                    }
+
+                    let start = ends[ends.len() - iterator.len() - 1];
+                    let (attributes, remainder) = match Attributes::split_leading(&text[start..]) {
+                        Some((attributes, rest)) => {
+                            let end = text.len() - rest.len();
+                            (attributes, Self::skip_to(&mut iterator, &text, &ends, end))
+                        }
+                        None => (Attributes::default(), String::new()),
+                    };
+
+                    expressions.push(Expression::Link(
+                        Box::new(Self::parse_expression(title_tokens)),
+                        Box::new(Self::parse_expression(link_tokens)),
+                        attributes,
+                    ));
+                    if !remainder.is_empty() {
+                        Self::append_to_last(&mut expressions, remainder);
+                    }
+                }
+                Token::LAngle => {
+                    let start = ends[ends.len() - iterator.len() - 1] - 1;
+
+                    match html::tag_length(&text[start..]) {
+                        Some(length) => {
+                            expressions
+                                .push(Expression::Html(text[start..start + length].to_string()));
+                            let remainder =
+                                Self::skip_to(&mut iterator, &text, &ends, start + length);
+                            if !remainder.is_empty() {
+                                Self::append_to_last(&mut expressions, remainder);
+                            }
+                        }
+                        None => Self::append_to_last(&mut expressions, "<".to_string()),
+                    }
+                }
+                Token::NewLine => break,
+                token => {
+                    Self::append_to_last(&mut expressions, token.to_string());
+                }
+            }
+        }
+
+        Self::tidy_expressions(expressions)
+    }
+
+    fn skip_to(iterator: &mut IntoIter<Token>, text: &str, ends: &[usize], end: usize) -> String {
+        let mut remainder = String::new();
+
+        while iterator.len() > 0 {
+            let index = ends.len() - iterator.len();
+            let start = index.checked_sub(1).map_or(0, |previous| ends[previous]);
+            if start >= end {
+                break;
+            }
+
+            iterator.next();
+            if ends[index] > end {
+                remainder = text[end..ends[index]].to_string();
+            }
+        }
+
+        remainder
+    }
+
+    fn append_to_last(expressions: &mut Vec<Expression>, string: String) {
+        let last = expressions.last_mut();
+        if let Some(Expression::Text(last_text)) = last {
+            *last_text += &string;
+        } else {
+            expressions.push(Expression::Text(string));
+        }
+    }
+
+    fn prepare_lines(iterator: &mut dyn Iterator<Item = Token>) -> Vec<Vec<Token>> {
+        let mut lines = vec![Vec::new()];
+        for token in iterator {
+            match token {
+                Token::NewLine => lines.push(Vec::new()),
+                token => {
+                    if let Some(last) = lines.last_mut() {
+                        last.push(token);
+                    }
+                }
+            }
+        }
+
+        lines
+    }
+
+    fn tidy_expressions(mut expressions: Vec<Expression>) -> Expression {
+        if expressions.len() == 1 {
+            expressions.remove(0)
+        } else {
+            Expression::Vec(expressions)
+        }
+    }
+
+    fn prepend_array<T>(mut array: Vec<T>, items: Vec<T>) -> Vec<T> {
+        let length = items.len();
+        for item in items {
+            array.push(item);
+        }
+        array.rotate_right(length);
+        array
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    Heading(usize, Expression, Attributes),
+    OrderedListItem(usize, Option<Task>, Expression, Vec<Statement>),
+    UnorderedListItem(char, Option<Task>, Expression, Vec<Statement>),
+    CodeBlock(String),
+    Table(Vec<Alignment>, Vec<Expression>, Vec<Vec<Expression>>),
+    FootnoteDefinition(String, Vec<Statement>),
+    DefinitionList(Vec<(Vec<Expression>, Vec<Vec<Statement>>)>),
+    BlockQuote(Vec<Statement>),
+    Alert(String, Vec<Statement>),
+    Container(String, Option<Expression>, Attributes, Vec<Statement>),
+    Html(String),
+    TableOfContents,
+    Section(Option<String>, Vec<Statement>),
+    Plain(Expression),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Task {
+    pub checked: bool,
+    pub line: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Alignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Expression {
+    Vec(Vec<Expression>),
+    Bold(Box<Expression>),
+    Italic(Box<Expression>),
+    BoldItalic(Box<Expression>),
+    Link(Box<Expression>, Box<Expression>, Attributes),
+    FootnoteReference(String),
+    Html(String),
+    Text(String),
+}
+
+impl Expression {
+    pub fn plain_text(&self) -> String {
+        match self {
+            Expression::Vec(expressions) => expressions
+                .iter()
+                .map(|expression| expression.plain_text())
+                .collect(),
+            Expression::Bold(expression)
+            | Expression::Italic(expression)
+            | Expression::BoldItalic(expression)
+            | Expression::Link(expression, _, _) => expression.plain_text(),
+            Expression::FootnoteReference(_) | Expression::Html(_) => String::new(),
+            Expression::Text(text) => text.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_heading() {
+        let parser = Parser::new(String::from(
+            "## Hello
+#Hi",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(
+                    2,
+                    Expression::Text("Hello".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Plain(Expression::Text("#Hi".to_string())),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_heading_levels() {
+        let parser = Parser::new(String::from(
+            "# one
+###### six
+####### seven
+#5 bolt
+#hashtag",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(
+                    1,
+                    Expression::Text("one".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    6,
+                    Expression::Text("six".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Plain(Expression::Text("####### seven".to_string())),
+                Statement::Plain(Expression::Text("#5 bolt".to_string())),
+                Statement::Plain(Expression::Text("#hashtag".to_string())),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_heading_indentation() {
+        let parser = Parser::new(String::from(
+            " ### foo
+  ## foo
+   # foo
+#                  foo                     ",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(
+                    3,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    2,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    1,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    1,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_heading_closing_sequence() {
+        let parser = Parser::new(String::from(
+            "## foo ##
+  ###   bar    ###
+# foo ##################################
+##### foo ##
+### foo ###     
+### foo ### b
+# foo#",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(
+                    2,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    3,
+                    Expression::Text("bar".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    1,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    5,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    3,
+                    Expression::Text("foo".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    3,
+                    Expression::Text("foo ### b".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    1,
+                    Expression::Text("foo#".to_string()),
+                    Attributes::default()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_empty_heading() {
+        let parser = Parser::new(String::from(
+            "## 
+#
+### ###",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(2, Expression::Text(String::new()), Attributes::default()),
+                Statement::Heading(1, Expression::Text(String::new()), Attributes::default()),
+                Statement::Heading(3, Expression::Text(String::new()), Attributes::default()),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_multiline_setext_heading() {
+        let parser = Parser::new(String::from(
+            "Intro
+
+Foo
+*bar*
+===",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Plain(Expression::Text("Intro".to_string())),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Heading(
+                    1,
+                    Expression::Vec(vec![
+                        Expression::Text("Foo".to_string()),
+                        Expression::Text("\n".to_string()),
+                        Expression::Italic(Box::new(Expression::Text("bar".to_string()))),
+                    ]),
+                    Attributes::default()
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_setext_heading() {
+        let parser = Parser::new(String::from(
+            "Title
+=====
+Subtitle
+  ---  
+
+---
+- item
+---
+Not a heading
+= =",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(
+                    1,
+                    Expression::Text("Title".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Heading(
+                    2,
+                    Expression::Text("Subtitle".to_string()),
+                    Attributes::default()
+                ),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Plain(Expression::Text("---".to_string())),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("item".to_string()),
+                    vec![]
+                ),
+                Statement::Plain(Expression::Text("---".to_string())),
+                Statement::Plain(Expression::Text("Not a heading".to_string())),
+                Statement::Plain(Expression::Text("= =".to_string())),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_attributes() {
+        let parser = Parser::new(String::from(
+            "## Install {#setup .important data-x=1}
+Usage {.wide}
+---
+# Sets {a} and [docs](https://example.test){rel=nofollow}!
+::: note Read *this* {#intro}
+:::",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Heading(
+                    2,
+                    Expression::Text("Install".to_string()),
+                    Attributes {
+                        id: Some("setup".to_string()),
+                        classes: vec!["important".to_string()],
+                        pairs: vec![("data-x".to_string(), "1".to_string())],
+                        ..Attributes::default()
+                    },
+                ),
+                Statement::Heading(
+                    2,
+                    Expression::Text("Usage".to_string()),
+                    Attributes {
+                        classes: vec!["wide".to_string()],
+                        ..Attributes::default()
+                    },
+                ),
+                Statement::Heading(
+                    1,
+                    Expression::Vec(vec![
+                        Expression::Text("Sets {a} and ".to_string()),
+                        Expression::Link(
+                            Box::new(Expression::Text("docs".to_string())),
+                            Box::new(Expression::Text("https://example.test".to_string())),
+                            Attributes {
+                                pairs: vec![("rel".to_string(), "nofollow".to_string())],
+                                ..Attributes::default()
+                            },
+                        ),
+                        Expression::Text("!".to_string()),
+                    ]),
+                    Attributes::default(),
+                ),
+                Statement::Container(
+                    "note".to_string(),
+                    Some(Expression::Vec(vec![
+                        Expression::Text("Read ".to_string()),
+                        Expression::Italic(Box::new(Expression::Text("this".to_string()))),
+                    ])),
+                    Attributes {
+                        id: Some("intro".to_string()),
+                        ..Attributes::default()
+                    },
+                    vec![],
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_code_block() {
+        let parser = Parser::new(String::from(
+            "    fn main() {
+          body();
+    
+      
+    }
+
+paragraph
+    continued
+    # not a heading
+    - not a list",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::CodeBlock("fn main() {\n      body();\n\n  \n}\n".to_string()),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Plain(Expression::Text("paragraph".to_string())),
+                Statement::Plain(Expression::Text("continued".to_string())),
+                Statement::Plain(Expression::Text("# not a heading".to_string())),
+                Statement::Plain(Expression::Text("- not a list".to_string())),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_tab_indentation() {
+        let parser = Parser::new(String::from(
+            "\tfoo\tbaz\t\tbim
+  \t\tbar
+#\tTitle\t#
+-\tone
+1.\ttwo
+- [x]\tthree",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::CodeBlock("foo\tbaz\t\tbim\n\tbar\n".to_string()),
+                Statement::Heading(
+                    1,
+                    Expression::Text("Title".to_string()),
+                    Attributes::default()
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("one".to_string()),
+                    vec![]
+                ),
+                Statement::OrderedListItem(1, None, Expression::Text("two".to_string()), vec![]),
+                Statement::UnorderedListItem(
+                    '-',
+                    Some(Task {
+                        checked: true,
+                        line: 6
+                    }),
+                    Expression::Text("three".to_string()),
+                    vec![]
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn strips_partial_tabs() {
+        let tokens = Parser::strip_indentation(
+            vec![
+                Token::WhiteSpace(1),
+                Token::Tab,
+                Token::Tab,
+                Token::Word("foo".to_string()),
+            ],
+            6,
+        );
+
+        assert_eq!(
+            tokens,
+            vec![Token::WhiteSpace(2), Token::Word("foo".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_setext_heading_starting_with_pipe() {
+        let parser = Parser::new(String::from(
+            "| a |
+---",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![Statement::Heading(
+                2,
+                Expression::Text("| a |".to_string()),
+                Attributes::default()
+            )]
+        )
+    }
 
-        while let Some(token) = iterator.next() {
-            match token {
-                Token::Word(word) => {
-                    Self::append_to_last(&mut expressions, word);
-                }
-                Token::Asterisk(left_count) => {
-                    let mut tokens = Vec::new();
-                    let mut right_count = 0;
+    #[test]
+    fn parses_table() {
+        let parser = Parser::new(String::from(
+            "a | b
+--- | ---
+1 | 2
 
-                    for token in iterator.by_ref() {
-                        match token {
-                            Token::Asterisk(count) => {
-                                right_count = count;
-                                break;
-                            }
-                            token => tokens.push(token),
-                        }
-                    }
+| x |
+| - |
+- list
+| not | a table |
+| --- |",
+        ));
+        let statements = parser.parse();
 
-                    if left_count > right_count {
-                        expressions.push(Expression::Text("*".repeat(left_count - right_count)));
-                    }
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Table(
+                    vec![Alignment::None, Alignment::None],
+                    vec![
+                        Expression::Text("a".to_string()),
+                        Expression::Text("b".to_string())
+                    ],
+                    vec![vec![
+                        Expression::Text("1".to_string()),
+                        Expression::Text("2".to_string())
+                    ]],
+                ),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Table(
+                    vec![Alignment::None],
+                    vec![Expression::Text("x".to_string())],
+                    vec![],
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("list".to_string()),
+                    vec![]
+                ),
+                Statement::Plain(Expression::Text("| not | a table |".to_string())),
+                Statement::Plain(Expression::Text("| --- |".to_string())),
+            ]
+        )
+    }
 
-                    let expression = match (left_count, right_count) {
-                        (3..=usize::MAX, 3..=usize::MAX) => {
-                            Expression::BoldItalic(Box::new(Self::parse_expression(tokens)))
-                        }
-                        (2..=usize::MAX, 2..=usize::MAX) => {
-                            Expression::Bold(Box::new(Self::parse_expression(tokens)))
-                        }
-                        (1..=usize::MAX, 1..=usize::MAX) => {
-                            Expression::Italic(Box::new(Self::parse_expression(tokens)))
-                        }
-                        _ => Expression::Text("*".repeat(left_count + right_count)),
-                    };
-                    expressions.push(expression);
+    #[test]
+    fn parses_footnotes() {
+        let parser = Parser::new(String::from(
+            "Text[^1] and [^note].
+[^1]: First
+[^note]: Long note
 
-                    if right_count > left_count {
-                        expressions.push(Expression::Text("*".repeat(right_count - left_count)));
-                    }
-                }
-                Token::LBracket => {
-                    let mut title_tokens = Vec::new();
-                    let mut link_tokens = Vec::new();
+    Second paragraph
+  [^x]:indented
+[^ no]: text",
+        ));
+        let statements = parser.parse();
 
-                    let mut found = false;
-                    for token in iterator.by_ref() {
-                        match token {
-                            Token::RBracket => {
-                                found = true;
-                                break;
-                            }
-                            token => title_tokens.push(token),
-                        }
-                    }
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Plain(Expression::Vec(vec![
+                    Expression::Text("Text".to_string()),
+                    Expression::FootnoteReference("1".to_string()),
+                    Expression::Text(" and ".to_string()),
+                    Expression::FootnoteReference("note".to_string()),
+                    Expression::Text(".".to_string()),
+                ])),
+                Statement::FootnoteDefinition(
+                    "1".to_string(),
+                    vec![Statement::Plain(Expression::Text("First".to_string()))],
+                ),
+                Statement::FootnoteDefinition(
+                    "note".to_string(),
+                    vec![
+                        Statement::Plain(Expression::Text("Long note".to_string())),
+                        Statement::Plain(Expression::Text(String::new())),
+                        Statement::Plain(Expression::Text("Second paragraph".to_string())),
+                    ],
+                ),
+                Statement::FootnoteDefinition(
+                    "x".to_string(),
+                    vec![Statement::Plain(Expression::Text("indented".to_string()))],
+                ),
+                Statement::Plain(Expression::Text("[^ no]: text".to_string())),
+            ]
+        )
+    }
 
-                    if !found {
-                        expressions.push(Self::parse_expression(Self::prepend_array(
-                            title_tokens,
-                            vec![Token::LBracket.to_word()],
-                        )));
-                        continue;
-                    }
+    #[test]
+    fn parses_definition_list() {
+        let parser = Parser::new(String::from(
+            ": lonely
 
-                    let next = iterator.next();
-                    let mut found = false;
-                    if let Some(Token::LParen) = &next {
-                        for token in iterator.by_ref() {
-                            match token {
-                                Token::RParen => {
-                                    found = true;
-                                    break;
-                                }
-                                token => link_tokens.push(token),
-                            }
-                        }
-                    }
+Apple
+Pomme
+: A fruit
+: A company
 
-                    if !found {
-                        let mut tokens =
-                            Self::prepend_array(title_tokens, vec![Token::LBracket.to_word()]);
-                        tokens.push(Token::RBracket.to_word());
-                        if let Some(next) = next {
-                            tokens.push(next);
-                        }
-                        tokens.append(&mut link_tokens);
-                        expressions.push(Self::parse_expression(tokens));
-                        continue;
-                    }
+    Founded in 1976
 
-                    expressions.push(Expression::Link(
-                        Box::new(Self::parse_expression(title_tokens)),
-                        Box::new(Self::parse_expression(link_tokens)),
-                    ));
-                }
-                Token::NewLine => break,
-                token => {
-                    Self::append_to_last(&mut expressions, token.to_string());
-                }
-            }
-        }
+Orange
+:   Another fruit
+: Citrus",
+        ));
+        let statements = parser.parse();
 
-        Self::tidy_expressions(expressions)
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Plain(Expression::Text(": lonely".to_string())),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::DefinitionList(vec![
+                    (
+                        vec![
+                            Expression::Text("Apple".to_string()),
+                            Expression::Text("Pomme".to_string()),
+                        ],
+                        vec![
+                            vec![Statement::Plain(Expression::Text("A fruit".to_string()))],
+                            vec![
+                                Statement::Plain(Expression::Text("A company".to_string())),
+                                Statement::Plain(Expression::Text(String::new())),
+                                Statement::Plain(Expression::Text("Founded in 1976".to_string())),
+                            ],
+                        ],
+                    ),
+                    (
+                        vec![Expression::Text("Orange".to_string())],
+                        vec![
+                            vec![Statement::Plain(Expression::Text(
+                                "Another fruit".to_string()
+                            ))],
+                            vec![Statement::Plain(Expression::Text("Citrus".to_string()))],
+                        ],
+                    ),
+                ]),
+            ]
+        )
     }
 
-    fn append_to_last(expressions: &mut Vec<Expression>, string: String) {
-        let last = expressions.last_mut();
-        if let Some(Expression::Text(last_text)) = last {
-            *last_text += &string;
-        } else {
-            expressions.push(Expression::Text(string));
-        }
-    }
+    #[test]
+    fn parses_blockquote() {
+        let parser = Parser::new(String::from(
+            "> # Quote
+>> nested
+lazy
+>
+> [!TIP]
 
-    fn prepare_lines(iterator: &mut dyn Iterator<Item = Token>) -> Vec<Vec<Token>> {
-        let mut lines = vec![Vec::new()];
-        for token in iterator {
-            match token {
-                Token::NewLine => lines.push(Vec::new()),
-                token => {
-                    if let Some(last) = lines.last_mut() {
-                        last.push(token);
-                    }
-                }
-            }
-        }
+text
+   > [!Warning]
+> Careful",
+        ));
+        let statements = parser.parse();
 
-        lines
+        assert_eq!(
+            statements,
+            vec![
+                Statement::BlockQuote(vec![
+                    Statement::Heading(
+                        1,
+                        Expression::Text("Quote".to_string()),
+                        Attributes::default()
+                    ),
+                    Statement::BlockQuote(vec![
+                        Statement::Plain(Expression::Text("nested".to_string())),
+                        Statement::Plain(Expression::Text("lazy".to_string())),
+                    ]),
+                    Statement::Plain(Expression::Text(String::new())),
+                    Statement::Plain(Expression::Text("[!TIP]".to_string())),
+                ]),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Plain(Expression::Text("text".to_string())),
+                Statement::Alert(
+                    "Warning".to_string(),
+                    vec![Statement::Plain(Expression::Text("Careful".to_string()))],
+                ),
+            ]
+        )
     }
 
-    fn tidy_expressions(mut expressions: Vec<Expression>) -> Expression {
-        if expressions.len() == 1 {
-            expressions.remove(0)
-        } else {
-            Expression::Vec(expressions)
-        }
-    }
+    #[test]
+    fn parses_container() {
+        let parser = Parser::new(String::from(
+            ":::: tabs
+::: tab *First*
+one
+:::
+:::warning
+two
+:::
+::::",
+        ));
+        let statements = parser.parse();
 
-    fn prepend_array<T>(mut array: Vec<T>, items: Vec<T>) -> Vec<T> {
-        let length = items.len();
-        for item in items {
-            array.push(item);
-        }
-        array.rotate_right(length);
-        array
+        assert_eq!(
+            statements,
+            vec![Statement::Container(
+                "tabs".to_string(),
+                None,
+                Attributes::default(),
+                vec![
+                    Statement::Container(
+                        "tab".to_string(),
+                        Some(Expression::Italic(Box::new(Expression::Text(
+                            "First".to_string()
+                        )))),
+                        Attributes::default(),
+                        vec![Statement::Plain(Expression::Text("one".to_string()))],
+                    ),
+                    Statement::Container(
+                        "warning".to_string(),
+                        None,
+                        Attributes::default(),
+                        vec![Statement::Plain(Expression::Text("two".to_string()))],
+                    ),
+                ],
+            )]
+        )
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub enum Statement {
-    Heading(usize, Expression),
-    OrderedListItem(usize, Expression),
-    UnorderedListItem(Expression),
-    TaskListItem(bool, Expression),
-    Plain(Expression),
-}
+    #[test]
+    fn parses_html() {
+        let parser = Parser::new(String::from(
+            "<div class=\"note\">
+*still html*
 
-#[derive(Debug, PartialEq)]
-pub enum Expression {
-    Vec(Vec<Expression>),
-    Bold(Box<Expression>),
-    Italic(Box<Expression>),
-    BoldItalic(Box<Expression>),
-    Link(Box<Expression>, Box<Expression>),
-    Text(String),
-}
+</div>
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+<!-- a
+comment --> trailing
+Some <span title=\"a < b\">inline</span> and 1 < 2
+<custom>
+
+<custom>
+paragraph
+<custom>",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::Html("<div class=\"note\">\n*still html*".to_string()),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Html("</div>".to_string()),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Html("<!-- a\ncomment --> trailing".to_string()),
+                Statement::Plain(Expression::Vec(vec![
+                    Expression::Text("Some ".to_string()),
+                    Expression::Html("<span title=\"a < b\">".to_string()),
+                    Expression::Text("inline".to_string()),
+                    Expression::Html("</span>".to_string()),
+                    Expression::Text(" and 1 < 2".to_string()),
+                ])),
+                Statement::Plain(Expression::Html("<custom>".to_string())),
+                Statement::Plain(Expression::Text(String::new())),
+                Statement::Html("<custom>\nparagraph\n<custom>".to_string()),
+            ]
+        )
+    }
 
     #[test]
-    fn parses_heading() {
+    fn parses_inline_html_inside_words() {
+        let parser = Parser::new(String::from("<b>bold</b>*it* a < b"));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![Statement::Plain(Expression::Vec(vec![
+                Expression::Html("<b>".to_string()),
+                Expression::Text("bold".to_string()),
+                Expression::Html("</b>".to_string()),
+                Expression::Italic(Box::new(Expression::Text("it".to_string()))),
+                Expression::Text(" a < b".to_string()),
+            ]))]
+        )
+    }
+
+    #[test]
+    fn parses_toc_placeholder() {
         let parser = Parser::new(String::from(
-            "## Hello
-#Hi",
+            "[TOC]
+  [[_TOC_]]  
+[TOC] here",
         ));
         let statements = parser.parse();
 
         assert_eq!(
             statements,
             vec![
-                Statement::Heading(2, Expression::Text("Hello".to_string())),
-                Statement::Plain(Expression::Text("#Hi".to_string())),
+                Statement::TableOfContents,
+                Statement::TableOfContents,
+                Statement::Plain(Expression::Text("[TOC] here".to_string())),
             ]
         )
     }
@@ -436,8 +1911,8 @@ mod tests {
         assert_eq!(
             statements,
             vec![
-                Statement::UnorderedListItem(Expression::Text("Hi".to_string())),
-                Statement::OrderedListItem(1, Expression::Text("Hello".to_string())),
+                Statement::UnorderedListItem('-', None, Expression::Text("Hi".to_string()), vec![]),
+                Statement::OrderedListItem(1, None, Expression::Text("Hello".to_string()), vec![]),
                 Statement::Plain(Expression::Text("1.Hello".to_string())),
                 Statement::Plain(Expression::Text("1 Hi".to_string())),
                 Statement::Plain(Expression::Text("1.".to_string())),
@@ -461,7 +1936,8 @@ mod tests {
             vec![
                 Statement::Plain(Expression::Link(
                     Box::new(Expression::Text("title".to_string())),
-                    Box::new(Expression::Text("https://example.test".to_string()))
+                    Box::new(Expression::Text("https://example.test".to_string())),
+                    Attributes::default(),
                 )),
                 Statement::Plain(Expression::Text("[title something else".to_string())),
                 Statement::Plain(Expression::Text("[title]https://example.test".to_string())),
@@ -473,6 +1949,36 @@ mod tests {
         )
     }
 
+    #[test]
+    fn parses_consecutive_link_attributes() {
+        let parser = Parser::new(String::from("[a](b){.x}*c* [d](e){#f}"));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![Statement::Plain(Expression::Vec(vec![
+                Expression::Link(
+                    Box::new(Expression::Text("a".to_string())),
+                    Box::new(Expression::Text("b".to_string())),
+                    Attributes {
+                        classes: vec!["x".to_string()],
+                        ..Attributes::default()
+                    },
+                ),
+                Expression::Italic(Box::new(Expression::Text("c".to_string()))),
+                Expression::Text(" ".to_string()),
+                Expression::Link(
+                    Box::new(Expression::Text("d".to_string())),
+                    Box::new(Expression::Text("e".to_string())),
+                    Attributes {
+                        id: Some("f".to_string()),
+                        ..Attributes::default()
+                    },
+                ),
+            ]))]
+        )
+    }
+
     #[test]
     fn parses_task_list() {
         let parsers = Parser::new(String::from(
@@ -487,11 +1993,132 @@ mod tests {
         assert_eq!(
             statements,
             vec![
-                Statement::TaskListItem(false, Expression::Text("Hi".to_string())),
-                Statement::TaskListItem(true, Expression::Text("Hello".to_string())),
-                Statement::UnorderedListItem(Expression::Text("[ hi there".to_string())),
-                Statement::UnorderedListItem(Expression::Text("[x hi there".to_string())),
-                Statement::UnorderedListItem(Expression::Text("[x]hi there".to_string())),
+                Statement::UnorderedListItem(
+                    '-',
+                    Some(Task {
+                        checked: false,
+                        line: 1
+                    }),
+                    Expression::Text("Hi".to_string()),
+                    vec![]
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    Some(Task {
+                        checked: true,
+                        line: 2
+                    }),
+                    Expression::Text("Hello".to_string()),
+                    vec![]
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("[ hi there".to_string()),
+                    vec![]
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("[x hi there".to_string()),
+                    vec![]
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("[x]hi there".to_string()),
+                    vec![]
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_nested_list() {
+        let parser = Parser::new(String::from(
+            "- [ ] one
+  * [x] two
+    text
+- three
+    code",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::UnorderedListItem(
+                    '-',
+                    Some(Task {
+                        checked: false,
+                        line: 1
+                    }),
+                    Expression::Text("one".to_string()),
+                    vec![
+                        Statement::UnorderedListItem(
+                            '*',
+                            Some(Task {
+                                checked: true,
+                                line: 2
+                            }),
+                            Expression::Text("two".to_string()),
+                            vec![]
+                        ),
+                        Statement::Plain(Expression::Text("text".to_string())),
+                    ]
+                ),
+                Statement::UnorderedListItem(
+                    '-',
+                    None,
+                    Expression::Text("three".to_string()),
+                    vec![]
+                ),
+                Statement::CodeBlock("code\n".to_string()),
+            ]
+        )
+    }
+
+    #[test]
+    fn parses_task_markers_in_any_list() {
+        let parser = Parser::new(String::from(
+            "1. [X] Plan
+2. Build
+* [ ] Ship
++ [x] Celebrate",
+        ));
+        let statements = parser.parse();
+
+        assert_eq!(
+            statements,
+            vec![
+                Statement::OrderedListItem(
+                    1,
+                    Some(Task {
+                        checked: true,
+                        line: 1
+                    }),
+                    Expression::Text("Plan".to_string()),
+                    vec![]
+                ),
+                Statement::OrderedListItem(2, None, Expression::Text("Build".to_string()), vec![]),
+                Statement::UnorderedListItem(
+                    '*',
+                    Some(Task {
+                        checked: false,
+                        line: 3
+                    }),
+                    Expression::Text("Ship".to_string()),
+                    vec![]
+                ),
+                Statement::UnorderedListItem(
+                    '+',
+                    Some(Task {
+                        checked: true,
+                        line: 4
+                    }),
+                    Expression::Text("Celebrate".to_string()),
+                    vec![]
+                ),
             ]
         )
     }
@@ -0,0 +1,618 @@
+use crate::attributes::Attributes;
+use crate::parser::Alignment;
+use std::collections::HashMap;
+
+pub trait Renderer {
+    fn text(&self, text: &str) -> String {
+        escape_text(text)
+    }
+
+    fn strong(&self, classes: &Classes, contents: &str) -> String {
+        format!(
+            "<strong{}>{}</strong>",
+            class_attribute(classes.get("strong")),
+            contents
+        )
+    }
+
+    fn emphasis(&self, classes: &Classes, contents: &str) -> String {
+        format!(
+            "<i{}>{}</i>",
+            class_attribute(classes.get("emphasis")),
+            contents
+        )
+    }
+
+    fn strong_emphasis(&self, classes: &Classes, contents: &str) -> String {
+        self.strong(classes, &self.emphasis(classes, contents))
+    }
+
+    fn link(
+        &self,
+        classes: &Classes,
+        href: &str,
+        attributes: &Attributes,
+        contents: &str,
+    ) -> String {
+        format!(
+            "<a href=\"{}\"{}>{}</a>",
+            href,
+            render_attributes(attributes, &class_list(classes.get("link"))),
+            contents
+        )
+    }
+
+    fn footnote_reference(
+        &self,
+        classes: &Classes,
+        label: &str,
+        id: &str,
+        number: usize,
+    ) -> String {
+        format!(
+            "<sup{}><a href=\"#fn-{}\" id=\"{}\">{}</a></sup>",
+            fixed_class_attribute("footnote-ref", classes.get("footnote_reference")),
+            escape(label),
+            escape(id),
+            number
+        )
+    }
+
+    fn heading(
+        &self,
+        classes: &Classes,
+        level: usize,
+        attributes: &Attributes,
+        contents: &str,
+        permalink: Option<&str>,
+    ) -> String {
+        let permalink = permalink
+            .map(|anchor| {
+                format!(
+                    " <a{} href=\"#{}\" aria-hidden=\"true\">#</a>",
+                    fixed_class_attribute("anchor", classes.get("anchor")),
+                    escape(anchor)
+                )
+            })
+            .unwrap_or_default();
+
+        format!(
+            "<h{}{}>{}{}</h{}>",
+            level,
+            render_attributes(attributes, &class_list(classes.get("heading"))),
+            contents,
+            permalink,
+            level
+        )
+    }
+
+    fn heading_number(&self, classes: &Classes, number: &str) -> String {
+        format!(
+            "<span{}>{}</span> ",
+            fixed_class_attribute("heading-number", classes.get("heading_number")),
+            escape(number)
+        )
+    }
+
+    fn paragraph(&self, classes: &Classes, contents: &str) -> String {
+        format!(
+            "<p{}>{}</p>",
+            class_attribute(classes.get("paragraph")),
+            contents
+        )
+    }
+
+    fn list_start(&self, classes: &Classes, ordered: bool, tasks: bool) -> String {
+        let (tag, element) = match (ordered, tasks) {
+            (true, false) => ("ol", "ordered_list"),
+            (false, false) => ("ul", "list"),
+            (true, true) => ("ol", "task_list"),
+            (false, true) => ("ul", "task_list"),
+        };
+        format!("<{}{}>", tag, class_attribute(classes.get(element)))
+    }
+
+    fn list_end(&self, _classes: &Classes, ordered: bool) -> String {
+        if ordered { "</ol>" } else { "</ul>" }.to_string()
+    }
+
+    fn list_item_start(&self, classes: &Classes) -> String {
+        format!("<li{}>", class_attribute(classes.get("list_item")))
+    }
+
+    fn task_list_item_start(
+        &self,
+        classes: &Classes,
+        checked: bool,
+        line: Option<usize>,
+    ) -> String {
+        let state = match line {
+            Some(line) => format!(
+                " data-line=\"{}\"{}",
+                line,
+                if checked { " checked" } else { "" }
+            ),
+            None => format!("{} disabled", if checked { " checked" } else { "" }),
+        };
+        format!(
+            "<li{}><input type=\"checkbox\"{}{}> ",
+            class_attribute(classes.get("task_item")),
+            class_attribute(classes.get("task_checkbox")),
+            state
+        )
+    }
+
+    fn list_item_end(&self, _classes: &Classes) -> String {
+        "</li>".to_string()
+    }
+
+    fn code_block(&self, classes: &Classes, code: &str) -> String {
+        format!(
+            "<pre{}><code{}>{}</code></pre>",
+            class_attribute(classes.get("code_block")),
+            class_attribute(classes.get("code")),
+            escape(code)
+        )
+    }
+
+    fn table_start(&self, classes: &Classes, head: &str, body: bool) -> String {
+        let body = if body {
+            format!("<tbody{}>", class_attribute(classes.get("table_body")))
+        } else {
+            String::new()
+        };
+        format!(
+            "<table{}><thead{}>{}</thead>{}",
+            class_attribute(classes.get("table")),
+            class_attribute(classes.get("table_head")),
+            head,
+            body
+        )
+    }
+
+    fn table_end(&self, _classes: &Classes, body: bool) -> String {
+        if body { "</tbody></table>" } else { "</table>" }.to_string()
+    }
+
+    fn table_row(&self, classes: &Classes, cells: &str) -> String {
+        format!(
+            "<tr{}>{}</tr>",
+            class_attribute(classes.get("table_row")),
+            cells
+        )
+    }
+
+    fn table_cell(
+        &self,
+        classes: &Classes,
+        header: bool,
+        alignment: &Alignment,
+        contents: &str,
+    ) -> String {
+        let (tag, element) = if header {
+            ("th", "table_header")
+        } else {
+            ("td", "table_cell")
+        };
+        let align = match alignment {
+            Alignment::None => "",
+            Alignment::Left => " align=\"left\"",
+            Alignment::Center => " align=\"center\"",
+            Alignment::Right => " align=\"right\"",
+        };
+
+        format!(
+            "<{}{}{}>{}</{}>",
+            tag,
+            class_attribute(classes.get(element)),
+            align,
+            contents,
+            tag
+        )
+    }
+
+    fn definition_list(&self, classes: &Classes, items: &str) -> String {
+        format!(
+            "<dl{}>{}</dl>",
+            class_attribute(classes.get("definition_list")),
+            items
+        )
+    }
+
+    fn definition_term(&self, classes: &Classes, contents: &str) -> String {
+        format!(
+            "<dt{}>{}</dt>",
+            class_attribute(classes.get("definition_term")),
+            contents
+        )
+    }
+
+    fn definition_description(&self, classes: &Classes, contents: &str) -> String {
+        format!(
+            "<dd{}>{}</dd>",
+            class_attribute(classes.get("definition_description")),
+            contents
+        )
+    }
+
+    fn blockquote(&self, classes: &Classes, contents: &str) -> String {
+        format!(
+            "<blockquote{}>{}</blockquote>",
+            class_attribute(classes.get("blockquote")),
+            contents
+        )
+    }
+
+    fn alert(&self, classes: &Classes, kind: &str, title: &str, contents: &str) -> String {
+        format!(
+            "<div{}><p{}>{}</p>{}</div>",
+            fixed_class_attribute(
+                &format!("markdown-alert markdown-alert-{}", kind),
+                classes.get("alert")
+            ),
+            fixed_class_attribute("markdown-alert-title", classes.get("alert_title")),
+            escape(title),
+            contents
+        )
+    }
+
+    fn container(
+        &self,
+        classes: &Classes,
+        kind: &str,
+        title: Option<&str>,
+        attributes: &Attributes,
+        contents: &str,
+    ) -> String {
+        let list: Vec<&str> = [Some(kind), classes.get("container")]
+            .into_iter()
+            .flatten()
+            .collect();
+        format!(
+            "<div{}>{}{}</div>",
+            render_attributes(attributes, &list),
+            title
+                .map(|title| {
+                    format!(
+                        "<p{}>{}</p>",
+                        fixed_class_attribute("container-title", classes.get("container_title")),
+                        title
+                    )
+                })
+                .unwrap_or_default(),
+            contents
+        )
+    }
+
+    fn details(
+        &self,
+        classes: &Classes,
+        open: bool,
+        summary: &str,
+        attributes: &Attributes,
+        contents: &str,
+    ) -> String {
+        format!(
+            "<details{}{}><summary{}>{}</summary>{}</details>",
+            render_attributes(attributes, &class_list(classes.get("details"))),
+            if open { " open" } else { "" },
+            class_attribute(classes.get("summary")),
+            summary,
+            contents
+        )
+    }
+
+    fn section(&self, classes: &Classes, attributes: &Attributes, contents: &str) -> String {
+        format!(
+            "<section{}>{}</section>",
+            render_attributes(attributes, &class_list(classes.get("section"))),
+            contents
+        )
+    }
+
+    fn toc(&self, classes: &Classes, nested: bool, items: &str) -> String {
+        let class = if nested {
+            class_attribute(classes.get("toc_list"))
+        } else {
+            fixed_class_attribute("toc", classes.get("toc"))
+        };
+
+        format!("<ul{}>{}</ul>", class, items)
+    }
+
+    fn toc_item(
+        &self,
+        classes: &Classes,
+        href: Option<&str>,
+        contents: &str,
+        children: &str,
+    ) -> String {
+        let class = class_attribute(classes.get("toc_item"));
+        match href {
+            Some(href) => format!(
+                "<li{}><a href=\"#{}\">{}</a>{}</li>",
+                class,
+                escape(href),
+                contents,
+                children
+            ),
+            None => format!("<li{}>{}{}</li>", class, contents, children),
+        }
+    }
+
+    fn footnotes(&self, classes: &Classes, items: &str) -> String {
+        format!(
+            "<section{}><ol{}>{}</ol></section>",
+            fixed_class_attribute("footnotes", classes.get("footnotes")),
+            class_attribute(classes.get("footnote_list")),
+            items
+        )
+    }
+
+    fn footnote(&self, classes: &Classes, label: &str, contents: &str, backrefs: &str) -> String {
+        format!(
+            "<li id=\"fn-{}\"{}>{}{}</li>",
+            escape(label),
+            class_attribute(classes.get("footnote")),
+            contents,
+            backrefs
+        )
+    }
+
+    fn footnote_backref(&self, classes: &Classes, id: &str) -> String {
+        format!(
+            " <a href=\"#{}\"{}>↩</a>",
+            escape(id),
+            fixed_class_attribute("footnote-backref", classes.get("footnote_backref"))
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct HtmlRenderer;
+
+impl Renderer for HtmlRenderer {}
+
+#[derive(Default)]
+pub struct Classes(pub HashMap<String, String>);
+
+impl Classes {
+    pub fn get(&self, element: &str) -> Option<&str> {
+        match self.0.get(element) {
+            Some(class) => Some(class),
+            None => default_class(element),
+        }
+    }
+}
+
+fn default_class(element: &str) -> Option<&'static str> {
+    match element {
+        "task_list" => Some("contains-task-list"),
+        "task_item" => Some("task-list-item"),
+        "task_checkbox" => Some("task-list-item-checkbox"),
+        _ => None,
+    }
+}
+
+fn class_list(class: Option<&str>) -> Vec<&str> {
+    class.into_iter().collect()
+}
+
+fn class_attribute(class: Option<&str>) -> String {
+    class
+        .map(|class| format!(" class=\"{}\"", escape(class)))
+        .unwrap_or_default()
+}
+
+fn fixed_class_attribute(fixed: &str, class: Option<&str>) -> String {
+    match class {
+        Some(class) => format!(" class=\"{} {}\"", escape(fixed), escape(class)),
+        None => format!(" class=\"{}\"", escape(fixed)),
+    }
+}
+
+pub fn render_attributes(attributes: &Attributes, classes: &[&str]) -> String {
+    let mut output = String::new();
+
+    if let Some(id) = &attributes.id {
+        output += &format!(" id=\"{}\"", escape(id));
+    }
+
+    let classes: Vec<&str> = classes
+        .iter()
+        .copied()
+        .chain(attributes.classes.iter().map(String::as_str))
+        .collect();
+    if !classes.is_empty() {
+        output += &format!(" class=\"{}\"", escape(&classes.join(" ")));
+    }
+
+    for (key, value) in &attributes.pairs {
+        if attributes.number.is_none() || key != "data-number" {
+            output += &format!(" {}=\"{}\"", key, escape(value));
+        }
+    }
+
+    if let Some(number) = &attributes.number {
+        output += &format!(" data-number=\"{}\"", escape(number));
+    }
+
+    output
+}
+
+pub fn escape_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for (index, char) in text.char_indices() {
+        match char {
+            '&' if is_entity(&text[index..]) => output.push('&'),
+            char => output += &escape(char.encode_utf8(&mut [0; 4])),
+        }
+    }
+
+    output
+}
+
+fn is_entity(text: &str) -> bool {
+    let Some(end) = text.find(';') else {
+        return false;
+    };
+    let name = &text[1..end];
+
+    match name.strip_prefix('#') {
+        Some(number) => match number.strip_prefix(['x', 'X']) {
+            Some(hex) => {
+                !hex.is_empty()
+                    && hex.len() <= 6
+                    && hex.chars().all(|char| char.is_ascii_hexdigit())
+            }
+            None => {
+                !number.is_empty()
+                    && number.len() <= 7
+                    && number.chars().all(|char| char.is_ascii_digit())
+            }
+        },
+        None => {
+            !name.is_empty()
+                && name.len() <= 32
+                && name.chars().all(|char| char.is_ascii_alphanumeric())
+        }
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            char => output.push(char),
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::Evaluator;
+    use crate::options::Options;
+
+    struct NofollowRenderer;
+
+    impl Renderer for NofollowRenderer {
+        fn link(&self, _: &Classes, href: &str, attributes: &Attributes, contents: &str) -> String {
+            format!(
+                "<a href=\"{}\" rel=\"nofollow\"{}>{}</a>",
+                href,
+                render_attributes(attributes, &[]),
+                contents
+            )
+        }
+
+        fn heading(
+            &self,
+            _: &Classes,
+            level: usize,
+            attributes: &Attributes,
+            contents: &str,
+            _: Option<&str>,
+        ) -> String {
+            format!(
+                "<h{}{}>§ {}</h{}>",
+                level,
+                render_attributes(attributes, &[]),
+                contents,
+                level
+            )
+        }
+    }
+
+    #[test]
+    fn overrides_single_nodes() {
+        let options = Options {
+            renderer: Box::new(NofollowRenderer),
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "# Links
+[*Docs*](https://example.test)
+1. one
+2. two",
+            ),
+            options,
+        );
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<h1 id=\"links\">§ Links</h1><p><a href=\"https://example.test\" rel=\"nofollow\"><i>Docs</i></a></p><ol><li>one</li><li>two</li></ol>"
+        );
+    }
+
+    #[test]
+    fn maps_classes_with_custom_renderers() {
+        let options = Options {
+            renderer: Box::new(NofollowRenderer),
+            classes: Classes(HashMap::from([(
+                "paragraph".to_string(),
+                "prose".to_string(),
+            )])),
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(String::from("hi"), options);
+
+        assert_eq!(evaluator.evaluate(), "<p class=\"prose\">hi</p>");
+    }
+
+    #[test]
+    fn maps_element_classes() {
+        let classes = Classes(
+            [
+                ("heading", "prose-h"),
+                ("task_list", "checklist"),
+                ("list_item", "item"),
+                ("table_header", "head-cell"),
+                ("table_cell", "cell"),
+                ("code", "source"),
+                ("toc", "outline"),
+            ]
+            .into_iter()
+            .map(|(element, class)| (element.to_string(), class.to_string()))
+            .collect(),
+        );
+
+        let options = Options {
+            classes,
+            ..Options::default()
+        };
+        let evaluator = Evaluator::with_options(
+            String::from(
+                "## Tasks {.wide}
+[TOC]
+- [ ] open
+- plain
+
+a | b
+--- | ---
+1 | 2
+
+    code",
+            ),
+            options,
+        );
+
+        assert_eq!(
+            evaluator.evaluate(),
+            "<h2 id=\"tasks\" class=\"prose-h wide\">Tasks</h2>\
+<ul class=\"toc outline\"><li><a href=\"#tasks\">Tasks</a></li></ul>\
+<ul class=\"checklist\"><li class=\"task-list-item\"><input type=\"checkbox\" class=\"task-list-item-checkbox\" disabled> open</li><li class=\"item\">plain</li></ul><p></p>\
+<table><thead><tr><th class=\"head-cell\">a</th><th class=\"head-cell\">b</th></tr></thead><tbody><tr><td class=\"cell\">1</td><td class=\"cell\">2</td></tr></tbody></table><p></p>\
+<pre><code class=\"source\">code\n</code></pre>"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use crate::parser::Statement;
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+pub fn build(statements: Vec<Statement>) -> Vec<Statement> {
+    collect(&mut statements.into_iter().peekable(), 0)
+}
+
+fn collect(iterator: &mut Peekable<IntoIter<Statement>>, level: usize) -> Vec<Statement> {
+    let mut statements = Vec::new();
+
+    while let Some(statement) = iterator.next_if(
+        |statement| !matches!(statement, Statement::Heading(other, _, _) if *other <= level),
+    ) {
+        match statement {
+            Statement::Heading(level, expression, mut attributes) => {
+                let id = attributes.id.take();
+                let mut children = vec![Statement::Heading(level, expression, attributes)];
+                children.append(&mut collect(iterator, level));
+                statements.push(Statement::Section(id, children));
+            }
+            statement => statements.push(statement),
+        }
+    }
+
+    statements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attributes::Attributes;
+    use crate::parser::{Expression, Parser};
+
+    fn heading(level: usize, text: &str) -> Statement {
+        Statement::Heading(
+            level,
+            Expression::Text(text.to_string()),
+            Attributes::default(),
+        )
+    }
+
+    fn plain(text: &str) -> Statement {
+        Statement::Plain(Expression::Text(text.to_string()))
+    }
+
+    #[test]
+    fn builds_section_tree() {
+        let statements = Parser::new(String::from(
+            "intro
+## A {#a}
+a
+### B
+b
+## C
+# D",
+        ))
+        .parse();
+
+        assert_eq!(
+            build(statements),
+            vec![
+                plain("intro"),
+                Statement::Section(
+                    Some("a".to_string()),
+                    vec![
+                        heading(2, "A"),
+                        plain("a"),
+                        Statement::Section(None, vec![heading(3, "B"), plain("b")]),
+                    ]
+                ),
+                Statement::Section(None, vec![heading(2, "C")]),
+                Statement::Section(None, vec![heading(1, "D")]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn new() -> Slugger {
+        Slugger::default()
+    }
+
+    pub fn slug(&mut self, text: &str) -> String {
+        let original: String = text
+            .to_lowercase()
+            .chars()
+            .filter(|char| char.is_alphanumeric() || matches!(char, ' ' | '-' | '_'))
+            .map(|char| if char == ' ' { '-' } else { char })
+            .collect();
+
+        let mut slug = original.clone();
+        while self.occurrences.contains_key(&slug) {
+            let count = self.occurrences.entry(original.clone()).or_default();
+            *count += 1;
+            slug = format!("{}-{}", original, count);
+        }
+        self.occurrences.insert(slug.clone(), 0);
+
+        slug
+    }
+
+    pub fn reserve(&mut self, slug: &str) {
+        self.occurrences.entry(slug.to_string()).or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugs_like_github() {
+        let mut slugger = Slugger::new();
+
+        assert_eq!(slugger.slug("Hello, World!"), "hello-world");
+        assert_eq!(slugger.slug("  Leading space"), "--leading-space");
+        assert_eq!(slugger.slug("Über café_déjà-vu"), "über-café_déjà-vu");
+        assert_eq!(slugger.slug("日本語 テスト"), "日本語-テスト");
+        assert_eq!(slugger.slug("C++ & Rust?"), "c--rust");
+    }
+
+    #[test]
+    fn deduplicates_slugs() {
+        let mut slugger = Slugger::new();
+
+        assert_eq!(slugger.slug("Install"), "install");
+        assert_eq!(slugger.slug("Install"), "install-1");
+        assert_eq!(slugger.slug("Install"), "install-2");
+        assert_eq!(slugger.slug("Install 1"), "install-1-1");
+
+        slugger.reserve("usage");
+        assert_eq!(slugger.slug("Usage"), "usage-1");
+    }
+}
@@ -0,0 +1,105 @@
+use crate::lexer::split_lines;
+
+pub fn toggle(source: &str, line: usize) -> Option<String> {
+    let start: usize = split_lines(source)
+        .take(line.checked_sub(1)?)
+        .map(str::len)
+        .sum();
+    let text = split_lines(&source[start..]).next()?;
+    let marker = start + marker_offset(text)?;
+
+    let checked = if source[marker..].starts_with(' ') {
+        "x"
+    } else {
+        " "
+    };
+    let mut output = source.to_string();
+    output.replace_range(marker..marker + 1, checked);
+
+    Some(output)
+}
+
+fn marker_offset(line: &str) -> Option<usize> {
+    let mut rest = line.strip_prefix('\u{feff}').unwrap_or(line);
+    loop {
+        let trimmed = rest.trim_start_matches([' ', '\t']);
+        match trimmed.strip_prefix('>') {
+            Some(quoted) => rest = quoted,
+            None => {
+                rest = trimmed;
+                break;
+            }
+        }
+    }
+
+    let rest = match rest.strip_prefix(['-', '*', '+']) {
+        Some(rest) => rest,
+        None => {
+            let number = rest.trim_start_matches(|char: char| char.is_ascii_digit());
+            if number.len() == rest.len() {
+                return None;
+            }
+            number.strip_prefix(['.', ')'])?
+        }
+    };
+
+    let content = rest.trim_start_matches([' ', '\t']);
+    if content.len() == rest.len() {
+        return None;
+    }
+
+    let marker = content.strip_prefix('[')?;
+    let after = marker.strip_prefix([' ', 'x', 'X'])?.strip_prefix(']')?;
+    if !after.is_empty() && !after.starts_with([' ', '\t', '\r', '\n']) {
+        return None;
+    }
+
+    Some(line.len() - marker.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evaluator::Evaluator;
+    use crate::options::Options;
+
+    #[test]
+    fn toggles_task_markers() {
+        let source =
+            "---\r\ntitle: Chores\r\n---\r\n- [x] Dishes\r\n\r\n> - [ ] Laundry\r\n- [ ]not a task";
+
+        assert_eq!(
+            toggle(source, 4),
+            Some("---\r\ntitle: Chores\r\n---\r\n- [ ] Dishes\r\n\r\n> - [ ] Laundry\r\n- [ ]not a task".to_string())
+        );
+        assert_eq!(
+            toggle(source, 6),
+            Some("---\r\ntitle: Chores\r\n---\r\n- [x] Dishes\r\n\r\n> - [x] Laundry\r\n- [ ]not a task".to_string())
+        );
+        assert_eq!(toggle(source, 5), None);
+        assert_eq!(toggle(source, 7), None);
+        assert_eq!(toggle(source, 8), None);
+        assert_eq!(toggle(source, 0), None);
+    }
+
+    #[test]
+    fn toggles_lines_reported_by_the_evaluator() {
+        let source = "---\rtitle: Chores\r---\r- [ ] Dishes\r\n- [x] Laundry\n";
+        let options = Options {
+            interactive_task_lists: true,
+            ..Options::default()
+        };
+        let output = Evaluator::with_options(source.to_string(), options).evaluate();
+
+        assert!(output.contains("data-line=\"4\"> Dishes"));
+        assert!(output.contains("data-line=\"5\" checked> Laundry"));
+        assert_eq!(
+            toggle(source, 5),
+            Some("---\rtitle: Chores\r---\r- [ ] Dishes\r\n- [ ] Laundry\n".to_string())
+        );
+        assert_eq!(
+            toggle(source, 4),
+            Some("---\rtitle: Chores\r---\r- [x] Dishes\r\n- [x] Laundry\n".to_string())
+        );
+    }
+}
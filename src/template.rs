@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::{fs, io};
+
+pub type Sink<'a, E> = dyn FnMut(&str) -> Result<(), E> + 'a;
+pub type Placeholder<'a, E> = dyn FnMut(&str, &mut Sink<E>) -> Result<(), E> + 'a;
+
+pub const THEME: &str = include_str!("theme.css");
+
+pub struct Template {
+    source: String,
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template::new(include_str!("template.html").to_string())
+    }
+}
+
+impl Template {
+    pub fn new(source: String) -> Template {
+        Template { source }
+    }
+
+    pub fn from_file(filepath: &str) -> io::Result<Template> {
+        fs::read_to_string(filepath).map(Template::new)
+    }
+
+    pub fn render(&self, values: &HashMap<String, String>) -> String {
+        let mut output = String::with_capacity(self.source.len());
+        let Ok(()) = self.stream(
+            &mut |text| {
+                output.push_str(text);
+                Ok::<(), Infallible>(())
+            },
+            &mut |key, write| values.get(key).map_or(Ok(()), |value| write(value)),
+        );
+
+        output
+    }
+
+    pub fn stream<E>(&self, write: &mut Sink<E>, value: &mut Placeholder<E>) -> Result<(), E> {
+        let mut rest = self.source.as_str();
+
+        while let Some(start) = rest.find("{{") {
+            write(&rest[..start])?;
+
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                rest = &rest[start..];
+                break;
+            };
+
+            value(after[..end].trim(), write)?;
+            rest = &after[end + 2..];
+        }
+
+        write(rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_placeholders() {
+        let template = Template::new(String::from(
+            "<title>{{title}}</title>{{ content }}{{ missing }}{{ unclosed",
+        ));
+        let values = [("title", "Guide"), ("content", "<p>Hi</p>")]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        assert_eq!(
+            template.render(&values),
+            "<title>Guide</title><p>Hi</p>{{ unclosed"
+        );
+    }
+}
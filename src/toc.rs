@@ -0,0 +1,151 @@
+use crate::attributes::Attributes;
+use crate::parser::{Expression, Statement};
+use crate::slug::Slugger;
+
+#[derive(Debug, PartialEq)]
+pub struct TocEntry {
+    pub level: usize,
+    pub text: String,
+    pub slug: String,
+    pub number: Option<String>,
+    pub children: Vec<TocEntry>,
+}
+
+pub fn outline(statements: &[Statement], min_depth: usize, max_depth: usize) -> Vec<TocEntry> {
+    let mut entries = Vec::new();
+    let mut slugger = Slugger::new();
+
+    for statement in statements {
+        if let Statement::Heading(_, _, Attributes { id: Some(id), .. }) = statement {
+            slugger.reserve(id);
+        }
+    }
+
+    for statement in statements {
+        if let Statement::Heading(level, expression, attributes) = statement {
+            let text = expression.plain_text().trim().to_string();
+            let slug = match &attributes.id {
+                Some(id) => id.clone(),
+                None => slugger.slug(&text),
+            };
+
+            if (min_depth..=max_depth).contains(level) {
+                insert(
+                    &mut entries,
+                    TocEntry {
+                        level: *level,
+                        text,
+                        slug,
+                        number: attributes.number.clone(),
+                        children: Vec::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    entries
+}
+
+fn insert(entries: &mut Vec<TocEntry>, entry: TocEntry) {
+    match entries.last_mut() {
+        Some(last) if last.level < entry.level => insert(&mut last.children, entry),
+        _ => entries.push(entry),
+    }
+}
+
+pub fn for_each_heading(
+    statements: &mut [Statement],
+    visit: &mut dyn FnMut(&mut usize, &mut Expression, &mut Attributes),
+) {
+    for statement in statements {
+        match statement {
+            Statement::Heading(level, expression, attributes) => {
+                visit(level, expression, attributes)
+            }
+            Statement::FootnoteDefinition(_, statements)
+            | Statement::BlockQuote(statements)
+            | Statement::Alert(_, statements)
+            | Statement::Container(_, _, _, statements)
+            | Statement::Section(_, statements) => for_each_heading(statements, visit),
+            Statement::DefinitionList(items) => {
+                for (_, definitions) in items {
+                    for definition in definitions {
+                        for_each_heading(definition, visit);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::Parser;
+
+    fn entry(level: usize, text: &str, slug: &str, children: Vec<TocEntry>) -> TocEntry {
+        TocEntry {
+            level,
+            text: text.to_string(),
+            slug: slug.to_string(),
+            number: None,
+            children,
+        }
+    }
+
+    #[test]
+    fn builds_nested_outline() {
+        let statements = Parser::new(String::from(
+            "# Guide
+## Install
+#### Linux
+## *Usage*
+### Flags
+## Install
+# Appendix {#usage}",
+        ))
+        .parse();
+
+        assert_eq!(
+            outline(&statements, 1, 6),
+            vec![
+                entry(
+                    1,
+                    "Guide",
+                    "guide",
+                    vec![
+                        entry(
+                            2,
+                            "Install",
+                            "install",
+                            vec![entry(4, "Linux", "linux", vec![])]
+                        ),
+                        entry(
+                            2,
+                            "Usage",
+                            "usage-1",
+                            vec![entry(3, "Flags", "flags", vec![])]
+                        ),
+                        entry(2, "Install", "install-1", vec![]),
+                    ]
+                ),
+                entry(1, "Appendix", "usage", vec![]),
+            ]
+        );
+        assert_eq!(
+            outline(&statements, 2, 3),
+            vec![
+                entry(2, "Install", "install", vec![]),
+                entry(
+                    2,
+                    "Usage",
+                    "usage-1",
+                    vec![entry(3, "Flags", "flags", vec![])]
+                ),
+                entry(2, "Install", "install-1", vec![]),
+            ]
+        );
+    }
+}